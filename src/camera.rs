@@ -0,0 +1,215 @@
+//! Pinhole camera with Brown–Conrady lens distortion.
+//!
+//! Normalized coordinates are `((u - cx) / fx, (v - cy) / fy)` for pixel
+//! coordinates `(u, v)` of an ideal, distortion free camera.
+
+use nalgebra::{Matrix3, Vector3};
+use geo::{Point, Pointf};
+use image::{Image, ImageGrayf};
+use pixel::{Pixel, gray};
+use eye::AlterType;
+use transform::{remap, InterplateType};
+
+/// Focal lengths and principal point, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intrinsics {
+    pub fx: f32,
+    pub fy: f32,
+    pub cx: f32,
+    pub cy: f32,
+}
+
+impl Intrinsics {
+    pub fn new(fx: f32, fy: f32, cx: f32, cy: f32) -> Intrinsics {
+        Intrinsics {
+            fx: fx,
+            fy: fy,
+            cx: cx,
+            cy: cy,
+        }
+    }
+
+    pub fn to_pixel(&self, p: Pointf) -> Pointf {
+        Point::new(p.x * self.fx + self.cx, p.y * self.fy + self.cy)
+    }
+
+    pub fn to_normalized(&self, p: Pointf) -> Pointf {
+        Point::new((p.x - self.cx) / self.fx, (p.y - self.cy) / self.fy)
+    }
+}
+
+/// Radial (`k1`, `k2`, `k3`) and tangential (`p1`, `p2`) coefficients.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Distortion {
+    pub k1: f32,
+    pub k2: f32,
+    pub k3: f32,
+    pub p1: f32,
+    pub p2: f32,
+}
+
+impl Distortion {
+    pub fn radial(k1: f32, k2: f32, k3: f32) -> Distortion {
+        Distortion {
+            k1: k1,
+            k2: k2,
+            k3: k3,
+            p1: 0f32,
+            p2: 0f32,
+        }
+    }
+
+    pub fn tangential(mut self, p1: f32, p2: f32) -> Distortion {
+        self.p1 = p1;
+        self.p2 = p2;
+        self
+    }
+
+    /// Distorts the normalized point `p`.
+    pub fn apply(&self, p: Pointf) -> Pointf {
+        let (x, y) = (p.x, p.y);
+        let r2 = x * x + y * y;
+        let radial = 1f32 + r2 * (self.k1 + r2 * (self.k2 + r2 * self.k3));
+        let xy = 2f32 * x * y;
+        Point::new(
+            x * radial + self.p1 * xy + self.p2 * (r2 + 2f32 * x * x),
+            y * radial + self.p1 * (r2 + 2f32 * y * y) + self.p2 * xy,
+        )
+    }
+
+    /// Inverts `apply` by fixed-point iteration.
+    pub fn remove(&self, p: Pointf) -> Pointf {
+        let mut u = p;
+        for _ in 0..20 {
+            let d = self.apply(u);
+            u = Point::new(u.x + p.x - d.x, u.y + p.y - d.y);
+        }
+        u
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub intrinsics: Intrinsics,
+    pub distortion: Distortion,
+}
+
+impl Camera {
+    pub fn new(intrinsics: Intrinsics, distortion: Distortion) -> Camera {
+        Camera {
+            intrinsics: intrinsics,
+            distortion: distortion,
+        }
+    }
+
+    /// Pixel position in the captured image of the ideal pixel `p`.
+    pub fn distort_point(&self, p: Pointf) -> Pointf {
+        let k = &self.intrinsics;
+        k.to_pixel(self.distortion.apply(k.to_normalized(p)))
+    }
+
+    /// Ideal pixel position of the captured pixel `p`.
+    pub fn undistort_point(&self, p: Pointf) -> Pointf {
+        let k = &self.intrinsics;
+        k.to_pixel(self.distortion.remove(k.to_normalized(p)))
+    }
+
+    /// Maps for `remap` producing a `width` x `height` undistorted image
+    /// with the same intrinsics.
+    pub fn undistort_maps(&self, width: usize, height: usize) -> (ImageGrayf, ImageGrayf) {
+        self.rectify_maps(&Matrix3::identity(), &self.intrinsics, width, height)
+    }
+
+    /// Maps for `remap` producing a `width` x `height` image seen by an ideal
+    /// camera with intrinsics `target`, rotated by `rotation` relative to
+    /// this one.
+    ///
+    /// Panics if `rotation` is not invertible.
+    pub fn rectify_maps(
+        &self,
+        rotation: &Matrix3<f32>,
+        target: &Intrinsics,
+        width: usize,
+        height: usize,
+    ) -> (ImageGrayf, ImageGrayf) {
+        let r_inv = rotation.try_inverse().expect(
+            "rotation matrix is not invertible",
+        );
+        let mut map_x = ImageGrayf::new(width, height);
+        let mut map_y = ImageGrayf::new(width, height);
+        for h in 0..height {
+            for w in 0..width {
+                let n = target.to_normalized(Point::new(w as f32, h as f32));
+                let ray = r_inv * Vector3::new(n.x, n.y, 1f32);
+                let n = Point::new(ray[0] / ray[2], ray[1] / ray[2]);
+                let p = self.intrinsics.to_pixel(self.distortion.apply(n));
+                map_x[(w, h)] = gray(p.x);
+                map_y[(w, h)] = gray(p.y);
+            }
+        }
+        (map_x, map_y)
+    }
+}
+
+/// Removes the lens distortion of `camera` from `src`. Pixels with no source
+/// are set to zero.
+pub fn undistort<T: Pixel>(src: &Image<T>, camera: &Camera, interp: InterplateType) -> Image<T> {
+    let (map_x, map_y) = camera.undistort_maps(src.width(), src.height());
+    remap(
+        src,
+        &map_x,
+        &map_y,
+        interp,
+        AlterType::Constant(T::zero()),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+    use image::ImageBGRA;
+    use imageio::{ImageIO, FreeImageIO};
+
+    fn assert_near(a: Pointf, b: Pointf) {
+        assert!((a.x - b.x).abs() < 1e-2 && (a.y - b.y).abs() < 1e-2, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_distortion_roundtrip() {
+        let camera = Camera::new(
+            Intrinsics::new(500f32, 480f32, 320f32, 240f32),
+            Distortion::radial(-0.2, 0.05, 0f32).tangential(0.001, -0.002),
+        );
+        for p in [Point::new(320f32, 240f32), Point::new(10f32, 20f32), Point::new(600f32, 400f32)]
+            .iter()
+        {
+            assert_near(camera.distort_point(camera.undistort_point(*p)), *p);
+        }
+        assert_near(camera.distort_point(Point::new(320f32, 240f32)), Point::new(320f32, 240f32));
+
+        let (map_x, map_y) = camera.undistort_maps(640, 480);
+        let p = camera.distort_point(Point::new(100f32, 50f32));
+        assert_near(Point::new(map_x[(100, 50)][0], map_y[(100, 50)][0]), p);
+    }
+
+    #[test]
+    fn test_undistort() {
+        let path = Path::new("./tests/cat.jpg");
+        let img: ImageBGRA = FreeImageIO::from_path(&path).unwrap();
+        let (w, h) = (img.width() as f32, img.height() as f32);
+        let camera = Camera::new(
+            Intrinsics::new(w, w, w / 2f32, h / 2f32),
+            Distortion::radial(-0.3, 0.1, 0f32),
+        );
+        let dst = undistort(&img, &camera, InterplateType::Bilinear);
+        assert_eq!(dst.size(), img.size());
+        let target = Path::new("/tmp/test-undistort-out.jpg");
+        FreeImageIO::save(&target, &dst).unwrap();
+
+        let identity = Camera::new(camera.intrinsics, Distortion::default());
+        let (map_x, map_y) = identity.undistort_maps(img.width(), img.height());
+        let dst = remap(&img, &map_x, &map_y, InterplateType::Nearest, AlterType::Extend);
+        assert_eq!(dst, img);
+    }
+}
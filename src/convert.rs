@@ -70,6 +70,89 @@ impl ColorMapper for MapGrayGrayf {
     }
 }
 
+// D65 reference white
+const WHITE: [f32; 3] = [0.950456, 1.0, 1.088754];
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1f32 / 2.4) - 0.055
+    }
+}
+
+/// CIE L*a*b* of an sRGB color, with L* in `[0, 100]`.
+pub fn bgr_to_lab(src: &BGR<u8>) -> [f32; 3] {
+    let b = srgb_to_linear(src[0] as f32 / 255f32);
+    let g = srgb_to_linear(src[1] as f32 / 255f32);
+    let r = srgb_to_linear(src[2] as f32 / 255f32);
+    let xyz = [
+        0.412453 * r + 0.357580 * g + 0.180423 * b,
+        0.212671 * r + 0.715160 * g + 0.072169 * b,
+        0.019334 * r + 0.119193 * g + 0.950227 * b,
+    ];
+    let f = |t: f32| if t > 0.008856 {
+        t.cbrt()
+    } else {
+        7.787 * t + 16f32 / 116f32
+    };
+    let fx = f(xyz[0] / WHITE[0]);
+    let fy = f(xyz[1] / WHITE[1]);
+    let fz = f(xyz[2] / WHITE[2]);
+    [116f32 * fy - 16f32, 500f32 * (fx - fy), 200f32 * (fy - fz)]
+}
+
+/// Inverse of `bgr_to_lab`, saturating colors outside the sRGB gamut.
+pub fn lab_to_bgr(lab: &[f32; 3]) -> BGR<u8> {
+    let fy = (lab[0] + 16f32) / 116f32;
+    let fx = fy + lab[1] / 500f32;
+    let fz = fy - lab[2] / 200f32;
+    let f_inv = |t: f32| if t > 0.206893 {
+        t * t * t
+    } else {
+        (t - 16f32 / 116f32) / 7.787
+    };
+    let x = f_inv(fx) * WHITE[0];
+    let y = f_inv(fy) * WHITE[1];
+    let z = f_inv(fz) * WHITE[2];
+    let r = 3.240479 * x - 1.537150 * y - 0.498535 * z;
+    let g = -0.969256 * x + 1.875992 * y + 0.041556 * z;
+    let b = 0.055648 * x - 0.204043 * y + 1.057311 * z;
+    let to_u8 = |v: f32| (linear_to_srgb(v.max(0f32).min(1f32)) * 255f32).round() as u8;
+    BGR([to_u8(b), to_u8(g), to_u8(r)])
+}
+
+/// Stores L*, a*, b* in channels 0, 1, 2 of a float BGR pixel.
+pub struct MapBGRLab;
+impl ColorMapper for MapBGRLab {
+    type SrcType = BGR<u8>;
+    type DstType = BGR<f32>;
+
+    #[inline(always)]
+    fn to(src: &Self::SrcType) -> Self::DstType {
+        BGR(bgr_to_lab(src))
+    }
+}
+
+pub struct MapLabBGR;
+impl ColorMapper for MapLabBGR {
+    type SrcType = BGR<f32>;
+    type DstType = BGR<u8>;
+
+    #[inline(always)]
+    fn to(src: &Self::SrcType) -> Self::DstType {
+        lab_to_bgr(&src.data)
+    }
+}
+
 pub fn convert<M>(src: &Image<M::SrcType>) -> Image<M::DstType>
 where
     M: ColorMapper,
@@ -173,4 +256,16 @@ mod test {
             assert_eq!(*p, src[(x, y)]);
         }
     }
+
+    #[test]
+    fn test_lab() {
+        let white = bgr_to_lab(&BGR([255, 255, 255]));
+        assert!((white[0] - 100f32).abs() < 1e-2);
+        assert!(white[1].abs() < 1e-2 && white[2].abs() < 1e-2);
+        let red = bgr_to_lab(&BGR([0, 0, 255]));
+        assert!((red[0] - 53.24).abs() < 0.1 && (red[1] - 80.09).abs() < 0.2);
+        for c in [BGR([0, 0, 0]), BGR([12, 200, 99]), BGR([255, 0, 128])].iter() {
+            assert_eq!(lab_to_bgr(&bgr_to_lab(c)), *c);
+        }
+    }
 }
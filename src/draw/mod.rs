@@ -0,0 +1,88 @@
+pub mod shape;
+pub mod text;
+
+pub use self::shape::{draw_line, draw_line_aa, draw_polyline, draw_rect, fill_rect, draw_circle,
+                      fill_circle, draw_ellipse, fill_ellipse, fill_polygon, draw_arrow};
+pub use self::text::{draw_text, text_size};
+
+use image::Image;
+use pixel::{Pixel, AlphaPixel};
+use sampler::saturate;
+use num::ToPrimitive;
+
+/// What the drawing functions paint with.
+pub trait Ink<P: Pixel> {
+    /// New value of `dst` when the ink covers the fraction `coverage` of it.
+    fn paint(&self, dst: P, coverage: f32) -> P;
+}
+
+/// A plain color replaces the pixels it fully covers.
+impl<P: Pixel> Ink<P> for P {
+    fn paint(&self, dst: P, coverage: f32) -> P {
+        if coverage >= 1f32 {
+            *self
+        } else {
+            self.blend(dst, coverage)
+        }
+    }
+}
+
+/// A color composited over the pixels by its own alpha, with straight
+/// (non-premultiplied) alpha on both sides.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alpha<P: AlphaPixel>(pub P);
+
+impl<P: AlphaPixel> Ink<P> for Alpha<P> {
+    fn paint(&self, dst: P, coverage: f32) -> P {
+        let ai = P::alpha_index();
+        let opaque = P::opaque();
+        let sa = self.0.raw()[ai].to_f32().unwrap() / opaque * coverage.min(1f32);
+        let da = dst.raw()[ai].to_f32().unwrap() / opaque;
+        let oa = sa + da * (1f32 - sa);
+        let mut out = dst;
+        if oa <= 0f32 {
+            return out;
+        }
+        for c in 0..P::channels() {
+            out.raw_mut()[c] = if c == ai {
+                saturate(oa * opaque)
+            } else {
+                let s = self.0.raw()[c].to_f32().unwrap();
+                let d = dst.raw()[c].to_f32().unwrap();
+                saturate((s * sa + d * da * (1f32 - sa)) / oa)
+            };
+        }
+        out
+    }
+}
+
+// paints the pixel at (x, y) if it lies inside `img`
+#[inline]
+fn plot<P: Pixel, I: Ink<P>>(img: &mut Image<P>, x: isize, y: isize, ink: &I, coverage: f32) {
+    if x >= 0 && y >= 0 && x < img.width() as isize && y < img.height() as isize {
+        let (x, y) = (x as usize, y as usize);
+        img[(x, y)] = ink.paint(img[(x, y)], coverage);
+    }
+}
+
+// paints every distinct point once, so translucent inks do not build up
+// where strokes overlap
+fn plot_points<P: Pixel, I: Ink<P>>(img: &mut Image<P>, points: &mut Vec<(isize, isize)>, ink: &I) {
+    points.sort();
+    points.dedup();
+    for &(x, y) in points.iter() {
+        plot(img, x, y, ink, 1f32);
+    }
+}
+
+// paints row `y` from `x0` to `x1` inclusive
+fn plot_span<P: Pixel, I: Ink<P>>(img: &mut Image<P>, x0: isize, x1: isize, y: isize, ink: &I) {
+    if y < 0 || y >= img.height() as isize {
+        return;
+    }
+    let x0 = x0.max(0);
+    let x1 = x1.min(img.width() as isize - 1);
+    for x in x0..x1 + 1 {
+        img[(x as usize, y as usize)] = ink.paint(img[(x as usize, y as usize)], 1f32);
+    }
+}
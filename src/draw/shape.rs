@@ -0,0 +1,344 @@
+use std::f32;
+use image::Image;
+use pixel::Pixel;
+use geo::{Pointi, Pointf, Recti};
+use draw::{Ink, plot, plot_points, plot_span};
+
+// appends the Bresenham line from (x0, y0) to (x1, y1), both ends included
+fn bresenham(x0: isize, y0: isize, x1: isize, y1: isize, out: &mut Vec<(isize, isize)>) {
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+    let (mut x, mut y) = (x0, y0);
+    let mut err = dx + dy;
+    loop {
+        out.push((x, y));
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+/// Draws the line from `p0` to `p1` with Bresenham's algorithm.
+pub fn draw_line<P: Pixel, I: Ink<P>>(img: &mut Image<P>, p0: Pointi, p1: Pointi, ink: I) {
+    let mut points = Vec::new();
+    bresenham(p0.x, p0.y, p1.x, p1.y, &mut points);
+    plot_points(img, &mut points, &ink);
+}
+
+/// Draws an anti-aliased line from `p0` to `p1` with Wu's algorithm.
+pub fn draw_line_aa<P: Pixel, I: Ink<P>>(img: &mut Image<P>, p0: Pointf, p1: Pointf, ink: I) {
+    let (mut x0, mut y0, mut x1, mut y1) = (p0.x, p0.y, p1.x, p1.y);
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+    if steep {
+        ::std::mem::swap(&mut x0, &mut y0);
+        ::std::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        ::std::mem::swap(&mut x0, &mut x1);
+        ::std::mem::swap(&mut y0, &mut y1);
+    }
+    let gradient = if x1 == x0 { 1f32 } else { (y1 - y0) / (x1 - x0) };
+    let put = |img: &mut Image<P>, x: f32, y: f32, c: f32| if steep {
+        plot(img, y as isize, x as isize, &ink, c);
+    } else {
+        plot(img, x as isize, y as isize, &ink, c);
+    };
+
+    // endpoints are weighted by how much of their pixel the line covers
+    let xend = x0.round();
+    let yend = y0 + gradient * (xend - x0);
+    let xgap = 1f32 - (x0 + 0.5).fract();
+    let xpx0 = xend;
+    put(img, xpx0, yend.floor(), (1f32 - yend.fract()) * xgap);
+    put(img, xpx0, yend.floor() + 1f32, yend.fract() * xgap);
+    let mut intery = yend + gradient;
+
+    let xend = x1.round();
+    let yend = y1 + gradient * (xend - x1);
+    let xgap = (x1 + 0.5).fract();
+    let xpx1 = xend;
+    put(img, xpx1, yend.floor(), (1f32 - yend.fract()) * xgap);
+    put(img, xpx1, yend.floor() + 1f32, yend.fract() * xgap);
+
+    let mut x = xpx0 + 1f32;
+    while x < xpx1 {
+        let y = intery.floor();
+        put(img, x, y, 1f32 - (intery - y));
+        put(img, x, y + 1f32, intery - y);
+        intery += gradient;
+        x += 1f32;
+    }
+}
+
+/// Draws the lines joining consecutive `points`, and the last point to the
+/// first one if `closed`.
+pub fn draw_polyline<P: Pixel, I: Ink<P>>(
+    img: &mut Image<P>,
+    points: &[Pointi],
+    closed: bool,
+    ink: I,
+) {
+    let mut out = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        let q = match points.get(i + 1) {
+            Some(q) => q,
+            None if closed && points.len() > 2 => &points[0],
+            None => break,
+        };
+        bresenham(p.x, p.y, q.x, q.y, &mut out);
+    }
+    if points.len() == 1 {
+        out.push((points[0].x, points[0].y));
+    }
+    plot_points(img, &mut out, &ink);
+}
+
+/// Draws the outline of the pixels covered by `rect`.
+pub fn draw_rect<P: Pixel, I: Ink<P>>(img: &mut Image<P>, rect: &Recti, ink: I) {
+    if rect.width <= 0 || rect.height <= 0 {
+        return;
+    }
+    let (x0, y0) = (rect.x, rect.y);
+    let (x1, y1) = (rect.x + rect.width - 1, rect.y + rect.height - 1);
+    let mut out = Vec::new();
+    bresenham(x0, y0, x1, y0, &mut out);
+    bresenham(x1, y0, x1, y1, &mut out);
+    bresenham(x1, y1, x0, y1, &mut out);
+    bresenham(x0, y1, x0, y0, &mut out);
+    plot_points(img, &mut out, &ink);
+}
+
+pub fn fill_rect<P: Pixel, I: Ink<P>>(img: &mut Image<P>, rect: &Recti, ink: I) {
+    for y in rect.y..rect.y + rect.height {
+        plot_span(img, rect.x, rect.x + rect.width - 1, y, &ink);
+    }
+}
+
+// outline of the axis aligned ellipse by the midpoint algorithm
+fn ellipse_points(cx: isize, cy: isize, rx: isize, ry: isize) -> Vec<(isize, isize)> {
+    let mut out = Vec::new();
+    if rx == 0 || ry == 0 {
+        bresenham(cx - rx, cy - ry, cx + rx, cy + ry, &mut out);
+        return out;
+    }
+    let (a2, b2) = ((rx * rx) as f64, (ry * ry) as f64);
+    let mut push4 = |x: isize, y: isize| {
+        out.push((cx + x, cy + y));
+        out.push((cx - x, cy + y));
+        out.push((cx + x, cy - y));
+        out.push((cx - x, cy - y));
+    };
+    let (mut x, mut y) = (0isize, ry);
+    let (mut dx, mut dy) = (0f64, 2f64 * a2 * y as f64);
+    // region where the slope is below 1
+    let mut d = b2 - a2 * ry as f64 + 0.25 * a2;
+    while dx < dy {
+        push4(x, y);
+        x += 1;
+        dx += 2f64 * b2;
+        if d < 0f64 {
+            d += dx + b2;
+        } else {
+            y -= 1;
+            dy -= 2f64 * a2;
+            d += dx - dy + b2;
+        }
+    }
+    let xf = x as f64 + 0.5;
+    let yf = (y - 1) as f64;
+    let mut d = b2 * xf * xf + a2 * yf * yf - a2 * b2;
+    while y >= 0 {
+        push4(x, y);
+        y -= 1;
+        dy -= 2f64 * a2;
+        if d > 0f64 {
+            d += a2 - dy;
+        } else {
+            x += 1;
+            dx += 2f64 * b2;
+            d += dx - dy + a2;
+        }
+    }
+    out
+}
+
+/// Draws the outline of the axis aligned ellipse with semi-axes `rx` and
+/// `ry` around `center`.
+pub fn draw_ellipse<P: Pixel, I: Ink<P>>(
+    img: &mut Image<P>,
+    center: Pointi,
+    rx: isize,
+    ry: isize,
+    ink: I,
+) {
+    let mut points = ellipse_points(center.x, center.y, rx.abs(), ry.abs());
+    plot_points(img, &mut points, &ink);
+}
+
+pub fn fill_ellipse<P: Pixel, I: Ink<P>>(
+    img: &mut Image<P>,
+    center: Pointi,
+    rx: isize,
+    ry: isize,
+    ink: I,
+) {
+    let (rx, ry) = (rx.abs(), ry.abs());
+    // the outline gives the extent of every row
+    let mut extent = vec![-1isize; 2 * ry as usize + 1];
+    for &(x, y) in ellipse_points(0, 0, rx, ry).iter() {
+        let e = &mut extent[(y + ry) as usize];
+        *e = (*e).max(x.abs());
+    }
+    for (i, &e) in extent.iter().enumerate() {
+        if e >= 0 {
+            let y = center.y + i as isize - ry;
+            plot_span(img, center.x - e, center.x + e, y, &ink);
+        }
+    }
+}
+
+pub fn draw_circle<P: Pixel, I: Ink<P>>(img: &mut Image<P>, center: Pointi, radius: isize, ink: I) {
+    draw_ellipse(img, center, radius, radius, ink);
+}
+
+pub fn fill_circle<P: Pixel, I: Ink<P>>(img: &mut Image<P>, center: Pointi, radius: isize, ink: I) {
+    fill_ellipse(img, center, radius, radius, ink);
+}
+
+/// Fills the polygon with vertices `points` by the even-odd rule.
+///
+/// Vertices are taken as pixel corners and a pixel is filled when its
+/// center lies inside, so the corners of a `Rect` fill exactly that rect.
+pub fn fill_polygon<P: Pixel, I: Ink<P>>(img: &mut Image<P>, points: &[Pointi], ink: I) {
+    if points.len() < 3 {
+        return;
+    }
+    let y_min = points.iter().map(|p| p.y).min().unwrap().max(0);
+    let y_max = points.iter().map(|p| p.y).max().unwrap().min(img.height() as isize);
+    let mut xs = Vec::new();
+    for y in y_min..y_max {
+        let yc = y as f32 + 0.5;
+        xs.clear();
+        for (i, p) in points.iter().enumerate() {
+            let q = &points[(i + 1) % points.len()];
+            let (y0, y1) = (p.y as f32, q.y as f32);
+            if (y0 <= yc && yc < y1) || (y1 <= yc && yc < y0) {
+                xs.push(p.x as f32 + (yc - y0) * (q.x - p.x) as f32 / (y1 - y0));
+            }
+        }
+        xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in xs.chunks(2) {
+            if pair.len() == 2 {
+                let x0 = (pair[0] - 0.5).ceil() as isize;
+                let x1 = (pair[1] - 0.5).ceil() as isize - 1;
+                plot_span(img, x0, x1, y, &ink);
+            }
+        }
+    }
+}
+
+/// Draws a line from `from` to `to` with an arrow head at `to`, whose sides
+/// are `tip_length` times the line length.
+pub fn draw_arrow<P: Pixel, I: Ink<P>>(
+    img: &mut Image<P>,
+    from: Pointi,
+    to: Pointi,
+    tip_length: f32,
+    ink: I,
+) {
+    let mut out = Vec::new();
+    bresenham(from.x, from.y, to.x, to.y, &mut out);
+    let (dx, dy) = ((from.x - to.x) as f32, (from.y - to.y) as f32);
+    let len = (dx * dx + dy * dy).sqrt() * tip_length;
+    if len > 0f32 {
+        let angle = dy.atan2(dx);
+        for &side in [-1f32, 1f32].iter() {
+            let a = angle + side * f32::consts::PI / 6f32;
+            let x = to.x + (len * a.cos()).round() as isize;
+            let y = to.y + (len * a.sin()).round() as isize;
+            bresenham(to.x, to.y, x, y, &mut out);
+        }
+    }
+    plot_points(img, &mut out, &ink);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{ImageGray, ImageBGRA};
+    use pixel::{gray, bgra};
+    use geo::{Point, Rect};
+    use draw::Alpha;
+
+    fn count(img: &ImageGray) -> usize {
+        img.iter().filter(|&(_, _, p)| p[0] != 0).count()
+    }
+
+    #[test]
+    fn test_shapes() {
+        let mut img = ImageGray::new(20, 20);
+        img.zero();
+        draw_line(&mut img, Point::new(2, 3), Point::new(12, 8), gray(255));
+        assert_eq!(count(&img), 11);
+        assert_eq!(img[(2, 3)], gray(255));
+        assert_eq!(img[(12, 8)], gray(255));
+
+        img.zero();
+        draw_rect(&mut img, &Rect::new(1, 1, 5, 4), gray(1));
+        assert_eq!(count(&img), 14);
+        img.zero();
+        fill_rect(&mut img, &Rect::new(-2, 18, 5, 5), gray(1));
+        assert_eq!(count(&img), 6);
+
+        // a polygon on the corners of a rect fills the rect
+        let mut a = ImageGray::new(20, 20);
+        let mut b = ImageGray::new(20, 20);
+        a.zero();
+        b.zero();
+        let corners = vec![Point::new(3, 4), Point::new(9, 4), Point::new(9, 7), Point::new(3, 7)];
+        fill_polygon(&mut a, &corners, gray(1));
+        fill_rect(&mut b, &Rect::new(3, 4, 6, 3), gray(1));
+        assert!(a == b);
+
+        img.zero();
+        fill_circle(&mut img, Point::new(10, 10), 6, gray(1));
+        let area = count(&img) as f32;
+        // the midpoint outline lies about half a pixel outside the radius
+        assert!((area - 6.5f32 * 6.5f32 * f32::consts::PI).abs() < 8f32, "{}", area);
+        img.zero();
+        draw_circle(&mut img, Point::new(10, 10), 6, gray(1));
+        for &(x, y) in [(16, 10), (4, 10), (10, 4), (10, 16)].iter() {
+            assert_eq!(img[(x, y)], gray(1));
+        }
+        assert_eq!(img[(10, 10)], gray(0));
+    }
+
+    #[test]
+    fn test_line_aa_and_alpha() {
+        let mut img = ImageGray::new(10, 10);
+        img.zero();
+        draw_line_aa(&mut img, Point::new(1f32, 1f32), Point::new(8f32, 4.5f32), gray(200));
+        assert!(img[(4, 2)][0] > 0 && img[(4, 2)][0] < 200);
+        assert_eq!(img[(1, 8)], gray(0));
+
+        // overlapping strokes of a translucent ink blend only once
+        let mut img = ImageBGRA::new(20, 20);
+        img.fill(&bgra(255, 0, 0, 255));
+        let ink = Alpha(bgra(0, 0, 255, 128));
+        draw_arrow(&mut img, Point::new(2, 10), Point::new(17, 10), 0.3, ink);
+        draw_circle(&mut img, Point::new(10, 10), 5, ink);
+        let mixed = bgra(127, 0, 128, 255);
+        assert_eq!(img[(17, 10)], mixed);
+        assert_eq!(img[(10, 5)], mixed);
+        assert_eq!(img[(0, 0)], bgra(255, 0, 0, 255));
+    }
+}
@@ -59,6 +59,11 @@ impl<P: Pixel> Eye<P> {
         self
     }
 
+    pub fn alter(mut self, alter_type: AlterType<P>) -> Self {
+        self.alter_type = alter_type;
+        self
+    }
+
     pub fn look(&self, img: &Image<P>) -> P {
         if 0 <= self.x && self.x < img.width() as isize && 0 <= self.y &&
             self.y < img.height() as isize
@@ -77,26 +82,24 @@ impl<P: Pixel> Eye<P> {
                 img[(x as usize, y as usize)].clone()
             }
             AlterType::Mirror => {
-                let mut x = self.x;
-                let mut y = self.y;
-                if x < 0 {
-                    x = -x;
-                };
-                if y < 0 {
-                    y = -y;
-                };
-                if x > img.width() as isize {
-                    x = img.width() as isize - (x % img.width() as isize);
-                };
-                if y > img.height() as isize {
-                    y = img.height() as isize - (y % img.height() as isize);
-                };
+                let x = mirror(self.x, img.width() as isize);
+                let y = mirror(self.y, img.height() as isize);
                 img[(x as usize, y as usize)].clone()
             }
         }
     }
 }
 
+// reflects `i` into `0..n` without repeating the edge pixel
+fn mirror(i: isize, n: isize) -> isize {
+    if n == 1 {
+        return 0;
+    }
+    let period = 2 * (n - 1);
+    let i = i.abs() % period;
+    if i < n { i } else { period - i }
+}
+
 impl<P: Pixel> Default for Eye<P> {
     fn default() -> Self {
         Eye {
@@ -129,5 +132,7 @@ mod test {
         assert_eq!(eye.extend().x(1).y(-100).look(&img), gray(2u8));
         assert_eq!(eye.extend().x(-100).y(-100).look(&img), gray(1u8));
         assert_eq!(eye.extend().x(100).y(100).look(&img), gray(9u8));
+        assert_eq!(eye.mirror().x(3).y(3).look(&img), gray(5u8));
+        assert_eq!(eye.alter(AlterType::Mirror).x(-4).y(0).look(&img), gray(1u8));
     }
 }
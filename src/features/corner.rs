@@ -0,0 +1,160 @@
+use image::{Image, ImageGrayf};
+use pixel::{Gray, gray};
+use traits::Primitive;
+use geo::Point;
+use op::edge_detect::sobel_gradients;
+use features::{KeyPoint, local_maxima, retain_best};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CornerResponse {
+    /// `det(M) - k * trace(M)^2` of the structure tensor `M`
+    Harris(f32),
+    /// smaller eigenvalue of the structure tensor (Shi and Tomasi)
+    MinEigen,
+}
+
+/// Corner detector on the structure tensor of the image gradients, summed
+/// over a `block_size` x `block_size` window.
+///
+/// Corners are local maxima of the response above `quality_level` times the
+/// strongest response, thinned to `min_distance` and `max_corners`.
+#[derive(Debug, Clone, Copy)]
+pub struct CornerDetector {
+    response: CornerResponse,
+    block_size: usize,
+    quality_level: f32,
+    min_distance: f32,
+    max_corners: usize,
+}
+
+impl CornerDetector {
+    pub fn harris() -> Self {
+        CornerDetector {
+            response: CornerResponse::Harris(0.04),
+            block_size: 3,
+            quality_level: 0.01,
+            min_distance: 1f32,
+            max_corners: 0,
+        }
+    }
+
+    /// Good features to track.
+    pub fn shi_tomasi() -> Self {
+        CornerDetector {
+            response: CornerResponse::MinEigen,
+            ..CornerDetector::harris()
+        }
+    }
+
+    pub fn response(mut self, response: CornerResponse) -> Self {
+        self.response = response;
+        self
+    }
+
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        assert_eq!(block_size & 1, 1);
+        self.block_size = block_size;
+        self
+    }
+
+    pub fn quality_level(mut self, quality_level: f32) -> Self {
+        self.quality_level = quality_level;
+        self
+    }
+
+    pub fn min_distance(mut self, min_distance: f32) -> Self {
+        self.min_distance = min_distance;
+        self
+    }
+
+    /// Keeps at most `max_corners` corners; zero keeps all of them.
+    pub fn max_corners(mut self, max_corners: usize) -> Self {
+        self.max_corners = max_corners;
+        self
+    }
+
+    /// Response of every pixel of `img`.
+    pub fn response_map<T: Primitive>(&self, img: &Image<Gray<T>>) -> ImageGrayf {
+        let (width, height) = img.size();
+        let (gx, gy) = sobel_gradients(img);
+        let half = (self.block_size / 2) as isize;
+        let mut dst = ImageGrayf::new(width, height);
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let (mut a, mut b, mut c) = (0f32, 0f32, 0f32);
+                for j in y - half..y + half + 1 {
+                    for i in x - half..x + half + 1 {
+                        let pi = clamp(i, width);
+                        let pj = clamp(j, height);
+                        let dx = gx[(pi, pj)][0];
+                        let dy = gy[(pi, pj)][0];
+                        a += dx * dx;
+                        b += dx * dy;
+                        c += dy * dy;
+                    }
+                }
+                let r = match self.response {
+                    CornerResponse::Harris(k) => a * c - b * b - k * (a + c) * (a + c),
+                    CornerResponse::MinEigen => {
+                        let d = ((a - c) * (a - c) / 4f32 + b * b).sqrt();
+                        (a + c) / 2f32 - d
+                    }
+                };
+                dst[(x as usize, y as usize)] = gray(r);
+            }
+        }
+        dst
+    }
+
+    pub fn detect<T: Primitive>(&self, img: &Image<Gray<T>>) -> Vec<KeyPoint> {
+        let response = self.response_map(img);
+        let values: Vec<f32> = response.iter().map(|(_, _, p)| p[0]).collect();
+        let max = values.iter().cloned().fold(0f32, f32::max);
+        let threshold = max * self.quality_level;
+        let corners = local_maxima(&values, img.width(), img.height(), threshold, 0)
+            .into_iter()
+            .map(|(x, y, r)| KeyPoint::new(Point::new(x as f32, y as f32), r, 1f32))
+            .collect();
+        retain_best(corners, self.min_distance, self.max_corners)
+    }
+}
+
+fn clamp(i: isize, len: usize) -> usize {
+    if i < 0 {
+        0
+    } else if i >= len as isize {
+        len - 1
+    } else {
+        i as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageGray;
+
+    // bright square on a dark background, with corners at (4, 4) and (11, 11)
+    fn square() -> ImageGray {
+        let mut img = ImageGray::new(16, 16);
+        for (x, y, p) in img.iter_mut() {
+            *p = gray(if x >= 4 && x < 12 && y >= 4 && y < 12 { 200 } else { 20 });
+        }
+        img
+    }
+
+    #[test]
+    fn test_corner_detectors() {
+        let img = square();
+        for detector in [CornerDetector::harris(), CornerDetector::shi_tomasi()].iter() {
+            let corners = detector.quality_level(0.1).min_distance(3f32).detect(&img);
+            assert_eq!(corners.len(), 4);
+            for kp in corners.iter() {
+                let near = |v: f32| (v - 4f32).abs() <= 1f32 || (v - 11f32).abs() <= 1f32;
+                assert!(near(kp.pt.x) && near(kp.pt.y), "{:?}", kp);
+            }
+            let best = detector.quality_level(0.1).min_distance(3f32).max_corners(2);
+            assert_eq!(best.detect(&img).len(), 2);
+        }
+    }
+}
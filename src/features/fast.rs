@@ -0,0 +1,181 @@
+use image::Image;
+use pixel::Gray;
+use traits::Primitive;
+use geo::Point;
+use features::{KeyPoint, local_maxima};
+
+/// Bresenham circle of radius 3, clockwise from the top.
+pub const CIRCLE: [(isize, isize); 16] = [
+    (0, -3),
+    (1, -3),
+    (2, -2),
+    (3, -1),
+    (3, 0),
+    (3, 1),
+    (2, 2),
+    (1, 3),
+    (0, 3),
+    (-1, 3),
+    (-2, 2),
+    (-3, 1),
+    (-3, 0),
+    (-3, -1),
+    (-2, -2),
+    (-1, -3),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FastType {
+    /// nine contiguous pixels of the circle
+    Fast9,
+    /// twelve contiguous pixels of the circle
+    Fast12,
+}
+
+impl FastType {
+    fn arc(&self) -> usize {
+        match *self {
+            FastType::Fast9 => 9,
+            FastType::Fast12 => 12,
+        }
+    }
+}
+
+/// Segment test detector of Rosten and Drummond: a pixel is a corner if a
+/// contiguous arc of the circle around it is entirely brighter, or entirely
+/// darker, than the pixel by more than `threshold`.
+///
+/// The response is the largest sum of absolute differences minus the
+/// threshold over the brighter or the darker pixels of the circle.
+#[derive(Debug, Clone, Copy)]
+pub struct FastDetector {
+    threshold: f32,
+    kind: FastType,
+    nonmax: bool,
+}
+
+impl FastDetector {
+    pub fn new(threshold: f32) -> Self {
+        FastDetector {
+            threshold: threshold,
+            kind: FastType::Fast9,
+            nonmax: true,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn kind(mut self, kind: FastType) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Enables non-maximum suppression of adjacent corners (default).
+    pub fn nonmax(mut self, nonmax: bool) -> Self {
+        self.nonmax = nonmax;
+        self
+    }
+
+    /// Segment test response of the pixel at `(x, y)`, zero if it is not a
+    /// corner. The pixel must be at least 3 pixels away from the border.
+    pub fn score<T: Primitive>(&self, img: &Image<Gray<T>>, x: usize, y: usize) -> f32 {
+        let at = |dx: isize, dy: isize| {
+            img[((x as isize + dx) as usize, (y as isize + dy) as usize)][0]
+                .to_f32()
+                .unwrap()
+        };
+        let p = at(0, 0);
+        let t = self.threshold;
+        // +1 brighter, -1 darker, 0 similar
+        let mut states = [0i8; 16];
+        let mut diffs = [0f32; 16];
+        for (k, &(dx, dy)) in CIRCLE.iter().enumerate() {
+            let d = at(dx, dy) - p;
+            diffs[k] = d;
+            states[k] = if d > t {
+                1
+            } else if d < -t {
+                -1
+            } else {
+                0
+            };
+        }
+        let arc = self.kind.arc();
+        let has_arc = |s: i8| {
+            let mut run = 0;
+            for k in 0..16 + arc {
+                if states[k % 16] == s {
+                    run += 1;
+                    if run >= arc {
+                        return true;
+                    }
+                } else {
+                    run = 0;
+                }
+            }
+            false
+        };
+        if !has_arc(1) && !has_arc(-1) {
+            return 0f32;
+        }
+        let bright: f32 = diffs.iter().filter(|&&d| d > t).map(|d| d - t).sum();
+        let dark: f32 = diffs.iter().filter(|&&d| d < -t).map(|d| -d - t).sum();
+        bright.max(dark)
+    }
+
+    pub fn detect<T: Primitive>(&self, img: &Image<Gray<T>>) -> Vec<KeyPoint> {
+        let (width, height) = img.size();
+        let mut scores = vec![0f32; width * height];
+        if width > 6 && height > 6 {
+            for y in 3..height - 3 {
+                for x in 3..width - 3 {
+                    scores[y * width + x] = self.score(img, x, y);
+                }
+            }
+        }
+        let found = if self.nonmax {
+            local_maxima(&scores, width, height, 0f32, 3)
+        } else {
+            scores
+                .iter()
+                .enumerate()
+                .filter(|&(_, &s)| s > 0f32)
+                .map(|(i, &s)| (i % width, i / width, s))
+                .collect()
+        };
+        found
+            .into_iter()
+            .map(|(x, y, s)| KeyPoint::new(Point::new(x as f32, y as f32), s, 1f32))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageGray;
+    use pixel::gray;
+
+    #[test]
+    fn test_fast() {
+        let mut img = ImageGray::new(15, 15);
+        img.fill(&gray(50));
+        img[(7, 7)] = gray(200);
+        let corners = FastDetector::new(20f32).detect(&img);
+        assert_eq!(corners.len(), 1);
+        assert_eq!(corners[0].pt, Point::new(7f32, 7f32));
+        assert_eq!(corners[0].response, 16f32 * 130f32);
+
+        // the corner of a quadrant passes FAST-9 but not FAST-12
+        for (x, y, p) in img.iter_mut() {
+            *p = gray(if x >= 7 && y >= 7 { 200 } else { 50 });
+        }
+        let fast9 = FastDetector::new(20f32).nonmax(false).detect(&img);
+        assert!(fast9.iter().any(|k| k.pt == Point::new(7f32, 7f32)));
+        let fast12 = FastDetector::new(20f32).kind(FastType::Fast12).detect(&img);
+        assert!(fast12.is_empty());
+    }
+}
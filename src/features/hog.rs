@@ -0,0 +1,236 @@
+use image::Image;
+use pixel::Gray;
+use traits::Primitive;
+use geo::{Point, Pointi};
+use op::edge_detect::sobel_gradients;
+
+/// Histogram of oriented gradients (Dalal and Triggs, 2005).
+///
+/// Gradient magnitudes are accumulated into `bins` unsigned orientation
+/// bins per `cell_size` x `cell_size` cell, interpolating between the two
+/// nearest bins. Blocks of `block_size` x `block_size` cells, taken every
+/// `block_stride` cells, are normalized with L2-Hys and concatenated.
+#[derive(Debug, Clone, Copy)]
+pub struct Hog {
+    cell_size: usize,
+    block_size: usize,
+    block_stride: usize,
+    bins: usize,
+    clip: f32,
+}
+
+impl Hog {
+    pub fn new() -> Self {
+        Hog {
+            cell_size: 8,
+            block_size: 2,
+            block_stride: 1,
+            bins: 9,
+            clip: 0.2,
+        }
+    }
+
+    /// Cell side in pixels.
+    pub fn cell_size(mut self, cell_size: usize) -> Self {
+        assert!(cell_size > 0);
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Block side in cells.
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        assert!(block_size > 0);
+        self.block_size = block_size;
+        self
+    }
+
+    /// Distance between blocks in cells.
+    pub fn block_stride(mut self, block_stride: usize) -> Self {
+        assert!(block_stride > 0);
+        self.block_stride = block_stride;
+        self
+    }
+
+    pub fn bins(mut self, bins: usize) -> Self {
+        assert!(bins > 0);
+        self.bins = bins;
+        self
+    }
+
+    /// Upper bound on the L2-normalized block values before renormalizing.
+    pub fn clip(mut self, clip: f32) -> Self {
+        self.clip = clip;
+        self
+    }
+
+    fn blocks(&self, cells: usize) -> usize {
+        if cells < self.block_size {
+            0
+        } else {
+            (cells - self.block_size) / self.block_stride + 1
+        }
+    }
+
+    /// Length of the descriptor of a `width` x `height` window.
+    pub fn descriptor_len(&self, width: usize, height: usize) -> usize {
+        let bx = self.blocks(width / self.cell_size);
+        let by = self.blocks(height / self.cell_size);
+        bx * by * self.block_size * self.block_size * self.bins
+    }
+
+    /// Descriptor of the whole of `window`.
+    pub fn compute<T: Primitive>(&self, window: &Image<Gray<T>>) -> Vec<f32> {
+        let cells = self.cell_histograms(window);
+        let (cw, ch) = (window.width() / self.cell_size, window.height() / self.cell_size);
+        self.window_descriptor(&cells, cw, 0, 0, cw, ch)
+    }
+
+    /// Descriptors of every `window` sized window of `img` whose top-left
+    /// corner is a multiple of `stride`, with that corner. The window and
+    /// stride must be multiples of the cell size, so cell histograms are
+    /// shared between windows.
+    pub fn compute_dense<T: Primitive>(
+        &self,
+        img: &Image<Gray<T>>,
+        window: (usize, usize),
+        stride: (usize, usize),
+    ) -> Vec<(Pointi, Vec<f32>)> {
+        let cs = self.cell_size;
+        assert!(window.0 % cs == 0 && window.1 % cs == 0);
+        assert!(stride.0 % cs == 0 && stride.1 % cs == 0 && stride.0 > 0 && stride.1 > 0);
+        let cells = self.cell_histograms(img);
+        let cw = img.width() / cs;
+        let mut out = Vec::new();
+        if window.0 > img.width() || window.1 > img.height() {
+            return out;
+        }
+        let nx = (img.width() - window.0) / stride.0 + 1;
+        let ny = (img.height() - window.1) / stride.1 + 1;
+        for y in (0..ny).map(|j| j * stride.1) {
+            for x in (0..nx).map(|i| i * stride.0) {
+                let desc = self.window_descriptor(
+                    &cells,
+                    cw,
+                    x / cs,
+                    y / cs,
+                    window.0 / cs,
+                    window.1 / cs,
+                );
+                out.push((Point::new(x as isize, y as isize), desc));
+            }
+        }
+        out
+    }
+
+    // histograms of the whole cells of `img`, in raster order
+    fn cell_histograms<T: Primitive>(&self, img: &Image<Gray<T>>) -> Vec<f32> {
+        use std::f32::consts::PI;
+        let cs = self.cell_size;
+        let (cw, ch) = (img.width() / cs, img.height() / cs);
+        let mut hist = vec![0f32; cw * ch * self.bins];
+        let (gx, gy) = sobel_gradients(img);
+        let bin_width = PI / self.bins as f32;
+        for y in 0..ch * cs {
+            for x in 0..cw * cs {
+                let dx = gx[(x, y)][0];
+                let dy = gy[(x, y)][0];
+                let mag = (dx * dx + dy * dy).sqrt();
+                if mag == 0f32 {
+                    continue;
+                }
+                let mut angle = dy.atan2(dx);
+                if angle < 0f32 {
+                    angle += PI;
+                }
+                // bins are centered at (i + 0.5) * bin_width
+                let t = angle / bin_width - 0.5;
+                let b0 = t.floor();
+                let w1 = t - b0;
+                let b0 = (b0 as isize + self.bins as isize) as usize % self.bins;
+                let b1 = (b0 + 1) % self.bins;
+                let off = ((y / cs) * cw + x / cs) * self.bins;
+                hist[off + b0] += mag * (1f32 - w1);
+                hist[off + b1] += mag * w1;
+            }
+        }
+        hist
+    }
+
+    // descriptor of the window of `wcw` x `wch` cells at cell (cx, cy)
+    fn window_descriptor(
+        &self,
+        cells: &[f32],
+        cw: usize,
+        cx: usize,
+        cy: usize,
+        wcw: usize,
+        wch: usize,
+    ) -> Vec<f32> {
+        let bins = self.bins;
+        let len = self.descriptor_len(wcw * self.cell_size, wch * self.cell_size);
+        let mut desc = Vec::with_capacity(len);
+        for by in 0..self.blocks(wch) {
+            for bx in 0..self.blocks(wcw) {
+                let start = desc.len();
+                for j in 0..self.block_size {
+                    for i in 0..self.block_size {
+                        let x = cx + bx * self.block_stride + i;
+                        let y = cy + by * self.block_stride + j;
+                        let off = (y * cw + x) * bins;
+                        desc.extend_from_slice(&cells[off..off + bins]);
+                    }
+                }
+                l2_hys(&mut desc[start..], self.clip);
+            }
+        }
+        desc
+    }
+}
+
+impl Default for Hog {
+    fn default() -> Self {
+        Hog::new()
+    }
+}
+
+fn l2_hys(block: &mut [f32], clip: f32) {
+    let eps = 1e-3f32;
+    let norm = (block.iter().map(|v| v * v).sum::<f32>() + eps * eps).sqrt();
+    block.iter_mut().for_each(|v| *v = (*v / norm).min(clip));
+    let norm = (block.iter().map(|v| v * v).sum::<f32>() + eps * eps).sqrt();
+    block.iter_mut().for_each(|v| *v /= norm);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageGray;
+    use pixel::gray;
+
+    #[test]
+    fn test_hog() {
+        let hog = Hog::new();
+        assert_eq!(hog.descriptor_len(64, 128), 3780);
+
+        // vertical edges only: all energy in the bins around 0 and 180 degrees
+        let mut img = ImageGray::new(16, 16);
+        for (x, _, p) in img.iter_mut() {
+            *p = gray(if x % 8 < 4 { 0 } else { 200 });
+        }
+        let desc = hog.compute(&img);
+        assert_eq!(desc.len(), 36);
+        for (i, v) in desc.iter().enumerate() {
+            let bin = i % 9;
+            assert!(bin == 0 || bin == 8 || *v == 0f32, "{} {}", i, v);
+        }
+        let norm: f32 = desc.iter().map(|v| v * v).sum();
+        assert!((norm - 1f32).abs() < 1e-3);
+
+        let mut big = ImageGray::new(40, 32);
+        big.fill(&gray(10));
+        let dense = hog.compute_dense(&big, (16, 16), (8, 8));
+        assert_eq!(dense.len(), 4 * 3);
+        assert_eq!(dense[5].0, Point::new(8, 8));
+        assert!(dense.iter().all(|&(_, ref d)| d.len() == 36));
+    }
+}
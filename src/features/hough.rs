@@ -0,0 +1,440 @@
+use std::f32::consts::PI;
+use image::{Image, ImageBinary};
+use pixel::Gray;
+use traits::Primitive;
+use geo::{Point, Pointf, Pointi};
+use math::utils::XorShiftRng;
+use op::edge_detect::sobel_gradients;
+
+/// Line `x * cos(theta) + y * sin(theta) = rho`, with `theta` in `[0, pi)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolarLine {
+    pub rho: f32,
+    pub theta: f32,
+    pub votes: usize,
+}
+
+/// Hough transform for lines over the non-zero pixels of a binary image,
+/// usually an edge map.
+#[derive(Debug, Clone, Copy)]
+pub struct HoughLines {
+    rho: f32,
+    theta: f32,
+    threshold: usize,
+    seed: u64,
+}
+
+impl HoughLines {
+    /// Accumulator resolution of `rho` pixels by `theta` radians; lines
+    /// need more than `threshold` votes.
+    pub fn new(rho: f32, theta: f32, threshold: usize) -> Self {
+        assert!(rho > 0f32 && theta > 0f32);
+        HoughLines {
+            rho: rho,
+            theta: theta,
+            threshold: threshold,
+            seed: 0,
+        }
+    }
+
+    /// Seed of the random point order of `detect_segments`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn tables(&self) -> (Vec<f32>, Vec<f32>) {
+        let n = (PI / self.theta).round().max(1f32) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 * self.theta;
+                (t.cos() / self.rho, t.sin() / self.rho)
+            })
+            .unzip()
+    }
+
+    fn rho_bins(&self, src: &ImageBinary) -> usize {
+        let diag = ((src.width() * src.width() + src.height() * src.height()) as f32).sqrt();
+        (2f32 * diag / self.rho).ceil() as usize + 1
+    }
+
+    /// Lines that are local maxima of the accumulator, most votes first.
+    pub fn detect(&self, src: &ImageBinary) -> Vec<PolarLine> {
+        let (cos, sin) = self.tables();
+        let nt = cos.len();
+        let nr = self.rho_bins(src);
+        let offset = (nr - 1) / 2;
+        let mut acc = vec![0usize; nt * nr];
+        for (x, y, p) in src.iter() {
+            if p[0] == 0 {
+                continue;
+            }
+            for t in 0..nt {
+                let r = (x as f32 * cos[t] + y as f32 * sin[t]).round() as isize;
+                acc[t * nr + (r + offset as isize) as usize] += 1;
+            }
+        }
+
+        // votes of the negated rho at row `t`, the neighbour across the seam
+        let seam = |t: usize, r: usize| if r <= 2 * offset {
+            acc[t * nr + 2 * offset - r]
+        } else {
+            0
+        };
+        let mut lines = Vec::new();
+        for t in 0..nt {
+            for r in 0..nr {
+                let v = acc[t * nr + r];
+                if v <= self.threshold {
+                    continue;
+                }
+                let left = if r > 0 { acc[t * nr + r - 1] } else { 0 };
+                let right = if r + 1 < nr { acc[t * nr + r + 1] } else { 0 };
+                // theta wraps around with rho negated; the first of equal
+                // lines across the seam is kept at theta = 0
+                let (up, down) = if nt == 1 {
+                    (0, 0)
+                } else if t == 0 {
+                    (0, acc[nr + r].max(seam(nt - 1, r)))
+                } else if t + 1 == nt {
+                    (acc[(t - 1) * nr + r].max(seam(0, r)), 0)
+                } else {
+                    (acc[(t - 1) * nr + r], acc[(t + 1) * nr + r])
+                };
+                if v > left && v >= right && v > up && v >= down {
+                    lines.push(PolarLine {
+                        rho: (r as isize - offset as isize) as f32 * self.rho,
+                        theta: t as f32 * self.theta,
+                        votes: v,
+                    });
+                }
+            }
+        }
+        lines.sort_by(|a, b| b.votes.cmp(&a.votes));
+        lines
+    }
+
+    /// Progressive probabilistic Hough transform (Matas et al., 2000): line
+    /// segments at least `min_length` long whose pixels are at most
+    /// `max_gap` apart, as pairs of end points.
+    pub fn detect_segments(
+        &self,
+        src: &ImageBinary,
+        min_length: f32,
+        max_gap: usize,
+    ) -> Vec<(Pointi, Pointi)> {
+        let (cos, sin) = self.tables();
+        let nt = cos.len();
+        let nr = self.rho_bins(src);
+        let offset = ((nr - 1) / 2) as isize;
+        let (width, height) = src.size();
+        let mut acc = vec![0usize; nt * nr];
+        let mut mask = vec![false; width * height];
+        let mut voted = vec![false; width * height];
+        let mut points = Vec::new();
+        for (x, y, p) in src.iter() {
+            if p[0] != 0 {
+                mask[y * width + x] = true;
+                points.push((x, y));
+            }
+        }
+        let mut rng = XorShiftRng::new(self.seed);
+        for i in (1..points.len()).rev() {
+            points.swap(i, rng.gen_range(i + 1));
+        }
+        let bin = |x: usize, y: usize, t: usize| {
+            let r = (x as f32 * cos[t] + y as f32 * sin[t]).round() as isize;
+            t * nr + (r + offset) as usize
+        };
+
+        let mut segments = Vec::new();
+        for &(x, y) in points.iter() {
+            if !mask[y * width + x] {
+                continue;
+            }
+            voted[y * width + x] = true;
+            let mut best = (0, 0);
+            for t in 0..nt {
+                let b = bin(x, y, t);
+                acc[b] += 1;
+                if acc[b] > best.0 {
+                    best = (acc[b], t);
+                }
+            }
+            if best.0 <= self.threshold {
+                continue;
+            }
+
+            // walk along the line in both directions, one pixel per step
+            // along its major axis
+            let theta = best.1 as f32 * self.theta;
+            let (dx, dy) = (-theta.sin(), theta.cos());
+            let major = dx.abs().max(dy.abs());
+            let (sx, sy) = (dx / major, dy / major);
+            let mut ends = [(x, y); 2];
+            for (k, &dir) in [1f32, -1f32].iter().enumerate() {
+                let (mut fx, mut fy) = (x as f32, y as f32);
+                let mut gap = 0;
+                loop {
+                    fx += dir * sx;
+                    fy += dir * sy;
+                    let (px, py) = (fx.round() as isize, fy.round() as isize);
+                    if px < 0 || py < 0 || px >= width as isize || py >= height as isize {
+                        break;
+                    }
+                    if mask[py as usize * width + px as usize] {
+                        gap = 0;
+                        ends[k] = (px as usize, py as usize);
+                    } else {
+                        gap += 1;
+                        if gap > max_gap {
+                            break;
+                        }
+                    }
+                }
+            }
+            let (ex, ey) = (
+                ends[0].0 as f32 - ends[1].0 as f32,
+                ends[0].1 as f32 - ends[1].1 as f32,
+            );
+            let good = (ex * ex + ey * ey).sqrt() >= min_length;
+
+            // clear the pixels of the segment, withdrawing their votes
+            let steps = ex.abs().max(ey.abs()).round() as usize;
+            for s in 0..steps + 1 {
+                let f = if steps == 0 { 0f32 } else { s as f32 / steps as f32 };
+                let px = (ends[1].0 as f32 + ex * f).round() as usize;
+                let py = (ends[1].1 as f32 + ey * f).round() as usize;
+                let idx = py * width + px;
+                if !mask[idx] {
+                    continue;
+                }
+                if good && voted[idx] {
+                    for t in 0..nt {
+                        acc[bin(px, py, t)] -= 1;
+                    }
+                }
+                if good {
+                    mask[idx] = false;
+                }
+            }
+            if good {
+                segments.push((
+                    Point::new(ends[1].0 as isize, ends[1].1 as isize),
+                    Point::new(ends[0].0 as isize, ends[0].1 as isize),
+                ));
+            }
+        }
+        segments
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub center: Pointf,
+    pub radius: f32,
+    /// number of edge pixels on the circle
+    pub votes: usize,
+}
+
+/// Gradient Hough transform for circles: edge pixels vote for centers along
+/// their gradient direction, then each center gets the radius supported by
+/// the most edge pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct HoughCircles {
+    edge_threshold: f32,
+    center_threshold: usize,
+    min_dist: f32,
+    min_radius: usize,
+    max_radius: usize,
+}
+
+impl HoughCircles {
+    pub fn new(min_radius: usize, max_radius: usize) -> Self {
+        assert!(min_radius > 0 && min_radius <= max_radius);
+        HoughCircles {
+            edge_threshold: 100f32,
+            center_threshold: 20,
+            min_dist: min_radius as f32,
+            min_radius: min_radius,
+            max_radius: max_radius,
+        }
+    }
+
+    /// Minimum Sobel gradient magnitude of edge pixels.
+    pub fn edge_threshold(mut self, edge_threshold: f32) -> Self {
+        self.edge_threshold = edge_threshold;
+        self
+    }
+
+    /// Minimum number of votes for a center.
+    pub fn center_threshold(mut self, center_threshold: usize) -> Self {
+        self.center_threshold = center_threshold;
+        self
+    }
+
+    /// Minimum distance between the centers of detected circles.
+    pub fn min_dist(mut self, min_dist: f32) -> Self {
+        self.min_dist = min_dist;
+        self
+    }
+
+    /// Circles with the most votes first.
+    pub fn detect<T: Primitive>(&self, img: &Image<Gray<T>>) -> Vec<Circle> {
+        let (width, height) = img.size();
+        let (gx, gy) = sobel_gradients(img);
+        let mut edges = Vec::new();
+        let mut acc = vec![0usize; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let (dx, dy) = (gx[(x, y)][0], gy[(x, y)][0]);
+                let mag = (dx * dx + dy * dy).sqrt();
+                if mag < self.edge_threshold {
+                    continue;
+                }
+                edges.push((x as f32, y as f32));
+                let (ux, uy) = (dx / mag, dy / mag);
+                for &dir in [1f32, -1f32].iter() {
+                    for r in self.min_radius..self.max_radius + 1 {
+                        let cx = (x as f32 + dir * ux * r as f32).round();
+                        let cy = (y as f32 + dir * uy * r as f32).round();
+                        if cx < 0f32 || cy < 0f32 || cx >= width as f32 || cy >= height as f32 {
+                            break;
+                        }
+                        acc[cy as usize * width + cx as usize] += 1;
+                    }
+                }
+            }
+        }
+
+        // local maxima of the center accumulator, strongest first
+        let mut centers = Vec::new();
+        for y in 1..height.saturating_sub(1) {
+            for x in 1..width.saturating_sub(1) {
+                let v = acc[y * width + x];
+                if v <= self.center_threshold {
+                    continue;
+                }
+                let l = acc[y * width + x - 1];
+                let r = acc[y * width + x + 1];
+                let u = acc[(y - 1) * width + x];
+                let d = acc[(y + 1) * width + x];
+                if v > l && v >= r && v > u && v >= d {
+                    centers.push((x, y, v));
+                }
+            }
+        }
+        centers.sort_by(|a, b| b.2.cmp(&a.2));
+
+        let mut circles: Vec<Circle> = Vec::new();
+        let nr = self.max_radius - self.min_radius + 1;
+        for &(x, y, _) in centers.iter() {
+            let center = Point::new(x as f32, y as f32);
+            let far = circles.iter().all(|c| {
+                let dx = c.center.x - center.x;
+                let dy = c.center.y - center.y;
+                dx * dx + dy * dy >= self.min_dist * self.min_dist
+            });
+            if !far {
+                continue;
+            }
+            let mut hist = vec![0usize; nr];
+            for &(ex, ey) in edges.iter() {
+                let d = ((ex - center.x).powi(2) + (ey - center.y).powi(2)).sqrt().round() as usize;
+                if d >= self.min_radius && d <= self.max_radius {
+                    hist[d - self.min_radius] += 1;
+                }
+            }
+            // compare the support of radii relative to their circumference
+            let mut best = 0;
+            for i in 1..nr {
+                let r0 = (best + self.min_radius) as f32;
+                let r1 = (i + self.min_radius) as f32;
+                if hist[i] as f32 / r1 > hist[best] as f32 / r0 {
+                    best = i;
+                }
+            }
+            if hist[best] > self.center_threshold {
+                circles.push(Circle {
+                    center: center,
+                    radius: (best + self.min_radius) as f32,
+                    votes: hist[best],
+                });
+            }
+        }
+        circles
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageGray;
+    use pixel::gray;
+
+    #[test]
+    fn test_hough_lines() {
+        let mut img = ImageBinary::new(40, 30);
+        img.zero();
+        for x in 5..35 {
+            img[(x, 10)] = gray(1);
+        }
+        for y in 2..28 {
+            if y != 15 {
+                img[(20, y)] = gray(1);
+            }
+        }
+        let hough = HoughLines::new(1f32, PI / 180f32, 20);
+        let lines = hough.detect(&img);
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].rho, lines[0].votes), (10f32, 30));
+        assert!((lines[0].theta - PI / 2f32).abs() < 1e-4);
+        assert_eq!((lines[1].rho, lines[1].theta, lines[1].votes), (20f32, 0f32, 25));
+
+        // a vertical line also votes at theta just below pi with rho negated;
+        // with an even number of rho bins that bin sits at 2 * offset - r
+        let mut tall = ImageBinary::new(3, 40);
+        tall.zero();
+        for y in 0..40 {
+            tall[(1, y)] = gray(1);
+        }
+        assert_eq!(hough.rho_bins(&tall) % 2, 0);
+        let lines = hough.detect(&tall);
+        assert_eq!((lines[0].rho, lines[0].theta, lines[0].votes), (1f32, 0f32, 40));
+        assert!(lines.iter().all(|l| l.rho != -1f32), "{:?}", lines);
+
+        // end points in raster order
+        let mut segments: Vec<_> = hough
+            .detect_segments(&img, 15f32, 2)
+            .into_iter()
+            .map(|(a, b)| if (a.y, a.x) <= (b.y, b.x) { (a, b) } else { (b, a) })
+            .collect();
+        segments.sort_by_key(|s| (s.0.y, s.0.x));
+        assert_eq!(
+            segments,
+            vec![
+                (Point::new(20, 2), Point::new(20, 27)),
+                (Point::new(5, 10), Point::new(34, 10)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hough_circles() {
+        let mut img = ImageGray::new(80, 60);
+        for (x, y, p) in img.iter_mut() {
+            let d1 = (x as f32 - 25f32).hypot(y as f32 - 30f32);
+            let d2 = (x as f32 - 60f32).hypot(y as f32 - 25f32);
+            *p = gray(if d1 <= 12f32 || d2 <= 8f32 { 220 } else { 30 });
+        }
+        let circles = HoughCircles::new(5, 15).detect(&img);
+        assert_eq!(circles.len(), 2);
+        let mut found: Vec<_> = circles.iter().map(|c| (c.center, c.radius)).collect();
+        found.sort_by(|a, b| a.0.x.partial_cmp(&b.0.x).unwrap());
+        let expected = [(Point::new(25f32, 30f32), 12f32), (Point::new(60f32, 25f32), 8f32)];
+        for (f, e) in found.iter().zip(expected.iter()) {
+            assert!((f.0.x - e.0.x).abs() <= 1f32 && (f.0.y - e.0.y).abs() <= 1f32, "{:?}", f);
+            assert!((f.1 - e.1).abs() <= 1f32, "{:?}", f);
+        }
+    }
+}
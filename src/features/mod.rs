@@ -0,0 +1,113 @@
+pub mod corner;
+pub mod fast;
+pub mod hog;
+pub mod hough;
+pub mod orb;
+
+pub use self::corner::CornerDetector;
+pub use self::fast::{FastDetector, FastType};
+pub use self::hog::Hog;
+pub use self::hough::{HoughLines, HoughCircles, PolarLine, Circle};
+pub use self::orb::{Orb, Descriptor, BruteForceMatcher, FeatureMatch};
+
+use geo::Pointf;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyPoint {
+    /// position in the coordinates of the input image
+    pub pt: Pointf,
+    /// detector response; larger is stronger
+    pub response: f32,
+    /// size of the level the keypoint was found on relative to the input
+    /// image, 1 for the input itself
+    pub scale: f32,
+    /// orientation in radians from the x axis towards the y axis; zero for
+    /// detectors that do not estimate it
+    pub angle: f32,
+}
+
+impl KeyPoint {
+    pub fn new(pt: Pointf, response: f32, scale: f32) -> KeyPoint {
+        KeyPoint {
+            pt: pt,
+            response: response,
+            scale: scale,
+            angle: 0f32,
+        }
+    }
+}
+
+// local maxima of `response` over the 3x3 neighbourhood that are above
+// `threshold`, ignoring a margin of `border` pixels; plateaus keep their
+// first pixel in raster order
+fn local_maxima(
+    response: &[f32],
+    width: usize,
+    height: usize,
+    threshold: f32,
+    border: usize,
+) -> Vec<(usize, usize, f32)> {
+    let mut found = Vec::new();
+    if width <= 2 * border || height <= 2 * border {
+        return found;
+    }
+    for y in border..height - border {
+        for x in border..width - border {
+            let v = response[y * width + x];
+            if v <= threshold {
+                continue;
+            }
+            let mut is_max = true;
+            'outer: for dy in -1isize..2 {
+                for dx in -1isize..2 {
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if (dx == 0 && dy == 0) || nx < 0 || ny < 0 || nx >= width as isize ||
+                        ny >= height as isize
+                    {
+                        continue;
+                    }
+                    let n = response[ny as usize * width + nx as usize];
+                    // earlier neighbours win ties
+                    let before = dy < 0 || (dy == 0 && dx < 0);
+                    if n > v || (before && n == v) {
+                        is_max = false;
+                        break 'outer;
+                    }
+                }
+            }
+            if is_max {
+                found.push((x, y, v));
+            }
+        }
+    }
+    found
+}
+
+/// Keeps the strongest keypoints so that no two are closer than
+/// `min_distance`, returning at most `max_count` of them (all if zero),
+/// strongest first.
+pub fn retain_best(
+    mut keypoints: Vec<KeyPoint>,
+    min_distance: f32,
+    max_count: usize,
+) -> Vec<KeyPoint> {
+    keypoints.sort_by(|a, b| b.response.partial_cmp(&a.response).unwrap());
+    let limit = if max_count == 0 { keypoints.len() } else { max_count };
+    let d2 = min_distance * min_distance;
+    let mut kept: Vec<KeyPoint> = Vec::new();
+    for kp in keypoints {
+        if kept.len() >= limit {
+            break;
+        }
+        let close = kept.iter().any(|k| {
+            let dx = k.pt.x - kp.pt.x;
+            let dy = k.pt.y - kp.pt.y;
+            dx * dx + dy * dy < d2
+        });
+        if !close {
+            kept.push(kp);
+        }
+    }
+    kept
+}
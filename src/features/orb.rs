@@ -0,0 +1,357 @@
+use image::{Image, ImageGray};
+use pixel::Gray;
+use geo::{Point, Pointf};
+use op::blur::GaussianKernel;
+use op::filter::Filter;
+use transform::{resize, InterplateType};
+use math::utils::XorShiftRng;
+use features::{FastDetector, KeyPoint};
+
+/// 256-bit binary descriptor.
+pub type Descriptor = [u8; 32];
+
+// radius of the patch used for orientation and description
+const HALF_PATCH: isize = 15;
+// test points lie within this radius before steering
+const PATTERN_RADIUS: f32 = 13f32;
+// keeps steered test points inside the level
+const BORDER: usize = 20;
+
+/// Oriented FAST and rotated BRIEF (Rublee et al., 2011).
+///
+/// FAST-9 corners are detected on every level of a pyramid scaled down by
+/// `scale_factor`, oriented by their intensity centroid and described by
+/// 256 intensity comparisons steered by that orientation.
+#[derive(Debug, Clone, Copy)]
+pub struct Orb {
+    n_features: usize,
+    scale_factor: f32,
+    n_levels: usize,
+    fast_threshold: f32,
+}
+
+impl Orb {
+    pub fn new() -> Self {
+        Orb {
+            n_features: 500,
+            scale_factor: 1.2,
+            n_levels: 8,
+            fast_threshold: 20f32,
+        }
+    }
+
+    /// Maximum number of features kept over all levels.
+    pub fn n_features(mut self, n_features: usize) -> Self {
+        self.n_features = n_features;
+        self
+    }
+
+    pub fn scale_factor(mut self, scale_factor: f32) -> Self {
+        assert!(scale_factor > 1f32);
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    pub fn n_levels(mut self, n_levels: usize) -> Self {
+        assert!(n_levels > 0);
+        self.n_levels = n_levels;
+        self
+    }
+
+    pub fn fast_threshold(mut self, fast_threshold: f32) -> Self {
+        self.fast_threshold = fast_threshold;
+        self
+    }
+
+    /// Detects keypoints in `img` and computes their descriptors;
+    /// `descriptors[i]` describes `keypoints[i]`.
+    pub fn detect_and_compute(&self, img: &ImageGray) -> (Vec<KeyPoint>, Vec<Descriptor>) {
+        let pattern = pattern();
+        let fast = FastDetector::new(self.fast_threshold);
+        let smooth = GaussianKernel::new(5, 2f32);
+
+        // share the features among levels in proportion to their area
+        let f = 1f32 / self.scale_factor;
+        let total = (1f32 - f.powi(self.n_levels as i32)) / (1f32 - f);
+        let mut keypoints = Vec::new();
+        let mut descriptors = Vec::new();
+        for level in 0..self.n_levels {
+            let scale = self.scale_factor.powi(level as i32);
+            let width = (img.width() as f32 / scale).round() as usize;
+            let height = (img.height() as f32 / scale).round() as usize;
+            if width <= 2 * BORDER || height <= 2 * BORDER {
+                break;
+            }
+            let layer = if level == 0 {
+                img.clone()
+            } else {
+                resize(img, width, height, InterplateType::Area)
+            };
+            let quota = (self.n_features as f32 * f.powi(level as i32) / total).round() as usize;
+
+            let mut found: Vec<KeyPoint> = fast.detect(&layer)
+                .into_iter()
+                .filter(|k| {
+                    let (x, y) = (k.pt.x as usize, k.pt.y as usize);
+                    x >= BORDER && y >= BORDER && x < width - BORDER && y < height - BORDER
+                })
+                .collect();
+            found.sort_by(|a, b| b.response.partial_cmp(&a.response).unwrap());
+            found.truncate(quota);
+
+            let blurred = smooth.filter(&layer);
+            for mut kp in found {
+                let (x, y) = (kp.pt.x as isize, kp.pt.y as isize);
+                kp.angle = intensity_centroid_angle(&layer, x, y);
+                descriptors.push(describe(&blurred, x, y, kp.angle, &pattern));
+                kp.pt = Point::new(kp.pt.x * scale, kp.pt.y * scale);
+                kp.scale = scale;
+                keypoints.push(kp);
+            }
+        }
+        (keypoints, descriptors)
+    }
+}
+
+impl Default for Orb {
+    fn default() -> Self {
+        Orb::new()
+    }
+}
+
+// fixed pseudo-random test pairs, isotropic Gaussian around the keypoint
+fn pattern() -> Vec<(Pointf, Pointf)> {
+    let mut rng = XorShiftRng::new(0x0b);
+    let sigma = (2 * HALF_PATCH + 1) as f32 / 5f32;
+    let mut coord = || loop {
+        // Box-Muller
+        let u = rng.next_f32().max(1e-7);
+        let v = rng.next_f32();
+        let r = (-2f32 * u.ln()).sqrt() * sigma;
+        let a = 2f32 * ::std::f32::consts::PI * v;
+        let p = Point::new(r * a.cos(), r * a.sin());
+        if p.x * p.x + p.y * p.y <= PATTERN_RADIUS * PATTERN_RADIUS {
+            return p;
+        }
+    };
+    (0..256).map(|_| (coord(), coord())).collect()
+}
+
+fn intensity_centroid_angle(img: &Image<Gray<u8>>, x: isize, y: isize) -> f32 {
+    let (mut m01, mut m10) = (0f32, 0f32);
+    for dy in -HALF_PATCH..HALF_PATCH + 1 {
+        for dx in -HALF_PATCH..HALF_PATCH + 1 {
+            if dx * dx + dy * dy > HALF_PATCH * HALF_PATCH {
+                continue;
+            }
+            let v = img[((x + dx) as usize, (y + dy) as usize)][0] as f32;
+            m10 += dx as f32 * v;
+            m01 += dy as f32 * v;
+        }
+    }
+    m01.atan2(m10)
+}
+
+fn describe(
+    img: &Image<Gray<u8>>,
+    x: isize,
+    y: isize,
+    angle: f32,
+    pattern: &[(Pointf, Pointf)],
+) -> Descriptor {
+    let (sin, cos) = angle.sin_cos();
+    let at = |p: Pointf| {
+        let px = (cos * p.x - sin * p.y).round() as isize;
+        let py = (sin * p.x + cos * p.y).round() as isize;
+        img[((x + px) as usize, (y + py) as usize)][0]
+    };
+    let mut desc = [0u8; 32];
+    for (i, &(a, b)) in pattern.iter().enumerate() {
+        if at(a) < at(b) {
+            desc[i / 8] |= 1 << (i % 8);
+        }
+    }
+    desc
+}
+
+pub fn hamming_distance(a: &Descriptor, b: &Descriptor) -> u32 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureMatch {
+    /// index into the query descriptors
+    pub query: usize,
+    /// index into the train descriptors
+    pub train: usize,
+    pub distance: u32,
+}
+
+/// Exhaustive nearest neighbour matching under the Hamming distance.
+#[derive(Debug, Clone, Copy)]
+pub struct BruteForceMatcher {
+    ratio: Option<f32>,
+    cross_check: bool,
+}
+
+impl BruteForceMatcher {
+    pub fn new() -> Self {
+        BruteForceMatcher {
+            ratio: None,
+            cross_check: false,
+        }
+    }
+
+    /// Lowe's ratio test: keeps a match only if it is closer than `ratio`
+    /// times the second nearest neighbour.
+    pub fn ratio(mut self, ratio: f32) -> Self {
+        self.ratio = Some(ratio);
+        self
+    }
+
+    /// Keeps a match only if the query is also the nearest neighbour of the
+    /// train descriptor.
+    pub fn cross_check(mut self, cross_check: bool) -> Self {
+        self.cross_check = cross_check;
+        self
+    }
+
+    pub fn match_descriptors(
+        &self,
+        query: &[Descriptor],
+        train: &[Descriptor],
+    ) -> Vec<FeatureMatch> {
+        let mut matches = Vec::new();
+        for (qi, q) in query.iter().enumerate() {
+            let (best, second) = nearest_two(q, train);
+            let (ti, distance) = match best {
+                Some(b) => b,
+                None => continue,
+            };
+            if let Some(ratio) = self.ratio {
+                if let Some((_, d2)) = second {
+                    if distance as f32 >= ratio * d2 as f32 {
+                        continue;
+                    }
+                }
+            }
+            if self.cross_check {
+                match nearest_two(&train[ti], query).0 {
+                    Some((back, _)) if back == qi => {}
+                    _ => continue,
+                }
+            }
+            matches.push(FeatureMatch {
+                query: qi,
+                train: ti,
+                distance: distance,
+            });
+        }
+        matches
+    }
+}
+
+impl Default for BruteForceMatcher {
+    fn default() -> Self {
+        BruteForceMatcher::new()
+    }
+}
+
+// nearest and second nearest neighbours of `d` in `set`
+fn nearest_two(d: &Descriptor, set: &[Descriptor]) -> (Option<(usize, u32)>, Option<(usize, u32)>) {
+    let mut best: Option<(usize, u32)> = None;
+    let mut second: Option<(usize, u32)> = None;
+    for (i, s) in set.iter().enumerate() {
+        let dist = hamming_distance(d, s);
+        if best.map_or(true, |(_, b)| dist < b) {
+            second = best;
+            best = Some((i, dist));
+        } else if second.map_or(true, |(_, b)| dist < b) {
+            second = Some((i, dist));
+        }
+    }
+    (best, second)
+}
+
+/// Positions of matched keypoints, ready for `Affine2D::affine_from_points`
+/// or a `RobustEstimator`.
+pub fn matched_points(
+    query: &[KeyPoint],
+    train: &[KeyPoint],
+    matches: &[FeatureMatch],
+) -> (Vec<Pointf>, Vec<Pointf>) {
+    matches
+        .iter()
+        .map(|m| (query[m.query].pt, train[m.train].pt))
+        .unzip()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+    use imageio::{ImageIO, FreeImageIO};
+    use math::affine::Affine2D;
+    use math::robust::{Ransac, RobustEstimator};
+    use transform::{rotation_matrix, warp_affine};
+    use eye::AlterType;
+    use pixel::gray;
+
+    #[test]
+    fn test_hamming_matching() {
+        let a = [0u8; 32];
+        let mut b = [0u8; 32];
+        b[0] = 0b1011;
+        b[31] = 0x80;
+        assert_eq!(hamming_distance(&a, &b), 4);
+
+        let mut c = [0xffu8; 32];
+        c[5] = 0;
+        let query = [a, c];
+        let train = [c, b];
+        let matches = BruteForceMatcher::new().cross_check(true).match_descriptors(
+            &query,
+            &train,
+        );
+        assert_eq!(matches.len(), 2);
+        assert_eq!((matches[0].query, matches[0].train, matches[0].distance), (0, 1, 4));
+        assert_eq!((matches[1].query, matches[1].train, matches[1].distance), (1, 0, 0));
+        // b is not much closer to a than c is to a
+        let strict = BruteForceMatcher::new().ratio(0.01);
+        assert_eq!(strict.match_descriptors(&query[..1], &train).len(), 0);
+    }
+
+    #[test]
+    fn test_orb_alignment() {
+        let path = Path::new("./tests/coins_speckle_0.1.tif");
+        let img: ImageGray = FreeImageIO::from_path(&path).unwrap();
+        let center = Point::new(img.width() as f32 / 2f32, img.height() as f32 / 2f32);
+        let m = rotation_matrix(0.3, center, 1f32);
+        let rotated = warp_affine(
+            &img,
+            &m,
+            img.width(),
+            img.height(),
+            InterplateType::Bilinear,
+            AlterType::Constant(gray(0)),
+        );
+
+        let orb = Orb::new().n_features(300);
+        let (kp1, d1) = orb.detect_and_compute(&img);
+        let (kp2, d2) = orb.detect_and_compute(&rotated);
+        assert!(kp1.len() > 20 && kp1.len() == d1.len());
+        let matches = BruteForceMatcher::new()
+            .cross_check(true)
+            .ratio(0.8)
+            .match_descriptors(&d1, &d2);
+        let (src, dst) = matched_points(&kp1, &kp2, &matches);
+        let estimate = Ransac::new()
+            .threshold(3f32)
+            .estimate::<Affine2D>(&src, &dst)
+            .unwrap();
+        let expected = Affine2D::from_matrix(&m).unwrap();
+        let p = estimate.model.apply([20f32, 30f32, 1f32]);
+        let q = expected.apply([20f32, 30f32, 1f32]);
+        assert!((p[0] - q[0]).abs() < 2f32 && (p[1] - q[1]).abs() < 2f32);
+    }
+}
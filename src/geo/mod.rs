@@ -1,9 +1,10 @@
 mod types;
 pub mod point;
 pub mod rect;
+pub mod polygon;
 
 pub use self::point::Point;
-pub use self::rect::Rect;
+pub use self::rect::{Rect, RotatedRect};
 
 pub type Pointi = Point<isize>;
 pub type Pointf = Point<f32>;
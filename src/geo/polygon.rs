@@ -0,0 +1,298 @@
+//! Geometry of point sequences: polygons, polylines and point clouds.
+
+use std::cmp::Ordering;
+
+use geo::{Point, Pointf, Pointi, Rect, Recti};
+use geo::rect::RotatedRect;
+use geo::types::GeoScalar;
+
+#[inline]
+fn to_f32<T: GeoScalar>(p: &Point<T>) -> Pointf {
+    Point::new(p.x.to_f32().unwrap(), p.y.to_f32().unwrap())
+}
+
+#[inline]
+fn cross(o: Pointf, a: Pointf, b: Pointf) -> f32 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+#[inline]
+fn dist(a: Pointf, b: Pointf) -> f32 {
+    ((a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y)).sqrt()
+}
+
+/// Length of a polyline, including the closing segment if `closed`.
+pub fn arc_length<T: GeoScalar>(points: &[Point<T>], closed: bool) -> f32 {
+    if points.len() < 2 {
+        return 0f32;
+    }
+    let mut len = 0f32;
+    for i in 1..points.len() {
+        len += dist(to_f32(&points[i - 1]), to_f32(&points[i]));
+    }
+    if closed {
+        len += dist(to_f32(&points[points.len() - 1]), to_f32(&points[0]));
+    }
+    len
+}
+
+/// Signed area of a polygon (shoelace formula). With the y axis pointing
+/// down, the area is positive for clockwise polygons.
+pub fn signed_area<T: GeoScalar>(points: &[Point<T>]) -> f32 {
+    let n = points.len();
+    let mut sum = 0f32;
+    for i in 0..n {
+        let a = to_f32(&points[i]);
+        let b = to_f32(&points[(i + 1) % n]);
+        sum += a.x * b.y - b.x * a.y;
+    }
+    sum / 2f32
+}
+
+/// Area enclosed by a polygon.
+pub fn contour_area<T: GeoScalar>(points: &[Point<T>]) -> f32 {
+    signed_area(points).abs()
+}
+
+/// Smallest upright rectangle containing all pixels of `points`.
+pub fn bounding_rect(points: &[Pointi]) -> Recti {
+    if points.is_empty() {
+        return Rect::new(0, 0, 0, 0);
+    }
+    let (mut x0, mut y0) = (points[0].x, points[0].y);
+    let (mut x1, mut y1) = (x0, y0);
+    for p in points.iter() {
+        x0 = x0.min(p.x);
+        y0 = y0.min(p.y);
+        x1 = x1.max(p.x);
+        y1 = y1.max(p.y);
+    }
+    Rect::new(x0, y0, x1 - x0 + 1, y1 - y0 + 1)
+}
+
+fn perpendicular_distance(p: Pointf, a: Pointf, b: Pointf) -> f32 {
+    let len = dist(a, b);
+    if len == 0f32 {
+        dist(p, a)
+    } else {
+        cross(a, b, p).abs() / len
+    }
+}
+
+fn douglas_peucker(points: &[Pointf], epsilon: f32, keep: &mut [bool]) {
+    let n = points.len();
+    if n < 3 {
+        return;
+    }
+    let (first, last) = (points[0], points[n - 1]);
+    let mut max_dist = 0f32;
+    let mut index = 0;
+    for i in 1..n - 1 {
+        let d = perpendicular_distance(points[i], first, last);
+        if d > max_dist {
+            max_dist = d;
+            index = i;
+        }
+    }
+    if max_dist > epsilon {
+        keep[index] = true;
+        douglas_peucker(&points[..index + 1], epsilon, &mut keep[..index + 1]);
+        douglas_peucker(&points[index..], epsilon, &mut keep[index..]);
+    }
+}
+
+/// Approximates a polyline with fewer vertices using the Douglas-Peucker
+/// algorithm, so that no point is farther than `epsilon` from the result.
+pub fn approx_poly_dp<T: GeoScalar>(
+    points: &[Point<T>],
+    epsilon: f32,
+    closed: bool,
+) -> Vec<Point<T>> {
+    let n = points.len();
+    if n < 3 {
+        return points.to_vec();
+    }
+    let pts: Vec<Pointf> = points.iter().map(to_f32).collect();
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    if closed {
+        // split the ring at the vertex farthest from the first one
+        let far = (1..n)
+            .max_by(|&i, &j| {
+                dist(pts[0], pts[i])
+                    .partial_cmp(&dist(pts[0], pts[j]))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap();
+        keep[far] = true;
+        douglas_peucker(&pts[..far + 1], epsilon, &mut keep[..far + 1]);
+        let mut tail: Vec<Pointf> = pts[far..].to_vec();
+        tail.push(pts[0]);
+        let mut tail_keep = vec![false; tail.len()];
+        douglas_peucker(&tail, epsilon, &mut tail_keep);
+        for i in 1..tail.len() - 1 {
+            keep[far + i] = tail_keep[i];
+        }
+    } else {
+        keep[n - 1] = true;
+        douglas_peucker(&pts, epsilon, &mut keep);
+    }
+    points
+        .iter()
+        .zip(keep.iter())
+        .filter(|&(_, k)| *k)
+        .map(|(p, _)| *p)
+        .collect()
+}
+
+/// Convex hull of a point set (Andrew's monotone chain), starting at the
+/// lowest-x point and running counterclockwise with the y axis pointing up.
+/// Collinear points on the hull boundary are dropped.
+pub fn convex_hull<T: GeoScalar>(points: &[Point<T>]) -> Vec<Point<T>> {
+    let mut pts = points.to_vec();
+    pts.sort_by(|a, b| {
+        a.x
+            .partial_cmp(&b.x)
+            .unwrap_or(Ordering::Equal)
+            .then(a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+    });
+    pts.dedup();
+    if pts.len() < 3 {
+        return pts;
+    }
+    let turn = |o: &Point<T>, a: &Point<T>, b: &Point<T>| cross(to_f32(o), to_f32(a), to_f32(b));
+
+    let mut hull: Vec<Point<T>> = Vec::with_capacity(2 * pts.len());
+    for p in pts.iter() {
+        while hull.len() >= 2 && turn(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= 0f32 {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    let lower = hull.len() + 1;
+    for p in pts.iter().rev().skip(1) {
+        while hull.len() >= lower && turn(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= 0f32 {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    hull.pop();
+    hull
+}
+
+/// Rotated rectangle of minimum area enclosing all `points` (rotating
+/// calipers over the convex hull).
+pub fn min_area_rect<T: GeoScalar>(points: &[Point<T>]) -> RotatedRect {
+    let hull: Vec<Pointf> = convex_hull(points).iter().map(to_f32).collect();
+    match hull.len() {
+        0 => return RotatedRect::new(Point::new(0f32, 0f32), 0f32, 0f32, 0f32),
+        1 => return RotatedRect::new(hull[0], 0f32, 0f32, 0f32),
+        _ => {}
+    }
+
+    let mut best: Option<(f32, RotatedRect)> = None;
+    for i in 0..hull.len() {
+        let a = hull[i];
+        let b = hull[(i + 1) % hull.len()];
+        let len = dist(a, b);
+        if len == 0f32 {
+            continue;
+        }
+        // unit vectors along and across the edge
+        let (ux, uy) = ((b.x - a.x) / len, (b.y - a.y) / len);
+        let (vx, vy) = (-uy, ux);
+        let (mut min_u, mut max_u, mut min_v, mut max_v) = (0f32, 0f32, 0f32, 0f32);
+        for p in hull.iter() {
+            let u = (p.x - a.x) * ux + (p.y - a.y) * uy;
+            let v = (p.x - a.x) * vx + (p.y - a.y) * vy;
+            min_u = min_u.min(u);
+            max_u = max_u.max(u);
+            min_v = min_v.min(v);
+            max_v = max_v.max(v);
+        }
+        let (width, height) = (max_u - min_u, max_v - min_v);
+        let area = width * height;
+        if best.as_ref().map_or(true, |&(a, _)| area < a) {
+            let cu = (min_u + max_u) / 2f32;
+            let cv = (min_v + max_v) / 2f32;
+            let center = Point::new(a.x + cu * ux + cv * vx, a.y + cu * uy + cv * vy);
+            best = Some((area, RotatedRect::new(center, width, height, uy.atan2(ux))));
+        }
+    }
+    best.unwrap().1
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_area_and_length() {
+        let square = vec![
+            Pointi::new(0, 0),
+            Pointi::new(4, 0),
+            Pointi::new(4, 4),
+            Pointi::new(0, 4),
+        ];
+        assert_eq!(contour_area(&square), 16f32);
+        assert_eq!(signed_area(&square), 16f32);
+        assert_eq!(arc_length(&square, true), 16f32);
+        assert_eq!(arc_length(&square, false), 12f32);
+        assert_eq!(bounding_rect(&square), Recti::new(0, 0, 5, 5));
+    }
+
+    #[test]
+    fn test_approx_poly() {
+        let line = vec![
+            Pointf::new(0f32, 0f32),
+            Pointf::new(1f32, 0.1f32),
+            Pointf::new(2f32, -0.1f32),
+            Pointf::new(3f32, 0f32),
+            Pointf::new(3f32, 1f32),
+            Pointf::new(3.1f32, 2f32),
+            Pointf::new(3f32, 3f32),
+        ];
+        let out = approx_poly_dp(&line, 0.5, false);
+        assert_eq!(out, vec![line[0], line[3], line[6]]);
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let pts = vec![
+            Pointi::new(0, 0),
+            Pointi::new(2, 1),
+            Pointi::new(4, 0),
+            Pointi::new(1, 2),
+            Pointi::new(4, 4),
+            Pointi::new(2, 4),
+            Pointi::new(0, 4),
+        ];
+        let hull = convex_hull(&pts);
+        assert_eq!(
+            hull,
+            vec![
+                Pointi::new(0, 0),
+                Pointi::new(4, 0),
+                Pointi::new(4, 4),
+                Pointi::new(0, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_area_rect() {
+        // a diamond is a square rotated by 45 degrees
+        let pts = vec![
+            Pointf::new(0f32, 2f32),
+            Pointf::new(2f32, 0f32),
+            Pointf::new(4f32, 2f32),
+            Pointf::new(2f32, 4f32),
+            Pointf::new(2f32, 2f32),
+        ];
+        let rect = min_area_rect(&pts);
+        assert!((rect.center.x - 2f32).abs() < 1e-4);
+        assert!((rect.center.y - 2f32).abs() < 1e-4);
+        assert!((rect.width * rect.height - 8f32).abs() < 1e-4);
+        assert!((rect.angle.abs() - ::std::f32::consts::FRAC_PI_4).abs() < 1e-4);
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use geo::Point;
+use geo::{Point, Pointf};
 use geo::types::GeoScalar;
 
 #[derive(PartialEq, Eq, Clone, Debug, Copy, Hash)]
@@ -70,6 +70,50 @@ impl<T: GeoScalar> fmt::Display for Rect<T> {
     }
 }
 
+/// Rectangle of size `width` x `height` centered at `center`, with its
+/// width side rotated by `angle` radians from the x axis.
+#[derive(PartialEq, Clone, Debug, Copy)]
+pub struct RotatedRect {
+    pub center: Pointf,
+    pub width: f32,
+    pub height: f32,
+    pub angle: f32,
+}
+
+impl RotatedRect {
+    pub fn new(center: Pointf, width: f32, height: f32, angle: f32) -> RotatedRect {
+        RotatedRect {
+            center: center,
+            width: width,
+            height: height,
+            angle: angle,
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width * self.height
+    }
+
+    /// Corner points, in the order `angle` rotates the upright
+    /// top-left, top-right, bottom-right and bottom-left corners into.
+    pub fn points(&self) -> [Pointf; 4] {
+        let (s, c) = self.angle.sin_cos();
+        let (hw, hh) = (self.width / 2f32, self.height / 2f32);
+        let corner = |dx: f32, dy: f32| {
+            Point::new(
+                self.center.x + dx * c - dy * s,
+                self.center.y + dx * s + dy * c,
+            )
+        };
+        [
+            corner(-hw, -hh),
+            corner(hw, -hh),
+            corner(hw, hh),
+            corner(-hw, hh),
+        ]
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -2,6 +2,8 @@ use std::slice;
 use num::traits::Bounded;
 use std::ops::{Index, IndexMut};
 use pixel::{Pixel, AlphaPixel, BGR, BGRA, Gray, Binary};
+use sampler::saturate;
+use num::ToPrimitive;
 
 #[derive(Debug)]
 pub enum ImageError {
@@ -9,6 +11,8 @@ pub enum ImageError {
     OutOfRegion,
     OutOfMemoryError,
     UnknownImageFormat,
+    /// the images of a binary operation differ in size
+    SizeMismatch,
     UnknownError,
 }
 
@@ -158,6 +162,56 @@ impl<'a, T: Pixel + 'a> Image<T> {
         img.data = data.to_vec();
         img
     }
+
+    /// Image of `f` applied to every pixel.
+    pub fn map<Q: Pixel, F: Fn(&T) -> Q>(&self, f: F) -> Image<Q> {
+        let mut data = Vec::with_capacity(self.w * self.h);
+        for y in 0..self.h {
+            data.extend(self.row(y)[..self.w].iter().map(&f));
+        }
+        Image {
+            w: self.w,
+            h: self.h,
+            stride: self.w,
+            data: data,
+        }
+    }
+
+    /// Image of `f` applied to the pixels of `self` and `other` at the same
+    /// position. Both images must have the same size.
+    pub fn zip_map<U, Q, F>(&self, other: &Image<U>, f: F) -> Image<Q>
+    where
+        U: Pixel,
+        Q: Pixel,
+        F: Fn(&T, &U) -> Q,
+    {
+        assert_eq!(self.size(), other.size());
+        let mut data = Vec::with_capacity(self.w * self.h);
+        for y in 0..self.h {
+            let (a, b) = (&self.row(y)[..self.w], &other.row(y)[..self.w]);
+            data.extend(a.iter().zip(b).map(|(p, q)| f(p, q)));
+        }
+        Image {
+            w: self.w,
+            h: self.h,
+            stride: self.w,
+            data: data,
+        }
+    }
+
+    /// Converts every channel `v` to `v * scale + offset` in the subpixel
+    /// type of `Q`, rounding and saturating for integer types. `Q` must have
+    /// as many channels as `T`.
+    pub fn convert_depth<Q: Pixel>(&self, scale: f32, offset: f32) -> Image<Q> {
+        assert_eq!(T::channels(), Q::channels());
+        self.map(|p| {
+            let mut q = Q::zero();
+            for (d, s) in q.raw_mut().iter_mut().zip(p.raw()) {
+                *d = saturate(s.to_f32().unwrap() * scale + offset);
+            }
+            q
+        })
+    }
 }
 
 pub trait AlphaImage {
@@ -217,6 +271,8 @@ pub type ImageGrayf = Image<Gray<f32>>;
 pub type ImageBGRf = Image<BGR<f32>>;
 pub type ImageBGRAf = Image<BGRA<f32>>;
 
+pub type ImageLabel = Image<Gray<u32>>;
+
 pub struct ImageIterator<'a, P>
 where
     P: Pixel + 'a,
@@ -432,6 +488,30 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_map() {
+        let img = gray_image![0, 100; 200, 255];
+        let wide: Image<Gray<u16>> = img.convert_depth(257f32, 0f32);
+        assert_eq!(wide[(1, 1)], gray(65535u16));
+        assert_eq!(wide[(0, 1)], gray(51400u16));
+        let f: ImageGrayf = img.convert_depth(1f32 / 255f32, 0f32);
+        let back: ImageGray = f.convert_depth(510f32, -100f32);
+        assert_eq!(back, gray_image![0, 100; 255, 255]);
+
+        let color = img.map(|p| bgr(p[0], 0, 255 - p[0]));
+        assert_eq!(color[(1, 0)], bgr(100, 0, 155));
+        let diff: ImageGrayf = img.zip_map(&back, |a, b| gray(b[0] as f32 - a[0] as f32));
+        assert_eq!(diff[(0, 1)], gray(55f32));
+
+        // out of range and NaN values saturate rather than panic
+        let mut wide = ImageGrayf::new(2, 1);
+        wide[(0, 0)] = gray(1e10f32);
+        wide[(1, 0)] = gray(::std::f32::NAN);
+        let narrow: Image<Gray<u32>> = wide.convert_depth(1f32, 0f32);
+        assert_eq!(narrow[(0, 0)], gray(u32::max_value()));
+        assert_eq!(narrow[(1, 0)], gray(0u32));
+    }
+
     #[test]
     fn test_binary_not() {
         let mut img =
@@ -14,10 +14,15 @@ pub mod traits;
 pub mod imageio;
 pub mod convert;
 pub mod transform;
+pub mod camera;
 pub mod geo;
 pub mod math;
 pub mod op;
 pub mod eye;
+pub mod features;
 pub mod pixel;
+pub mod sampler;
+pub mod segment;
+pub mod draw;
 
 pub use imageio::{ImageIO, FreeImageIO};
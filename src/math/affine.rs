@@ -18,6 +18,22 @@ impl Affine2D {
         }
     }
 
+    /// Creates the transform from the top two rows of its matrix.
+    pub fn from_matrix(m: &[[f32; 3]; 2]) -> Option<Affine2D> {
+        let t: Matrix3<f32> = Matrix3::new(
+            m[0][0],
+            m[0][1],
+            m[0][2],
+            m[1][0],
+            m[1][1],
+            m[1][2],
+            0f32,
+            0f32,
+            1f32,
+        );
+        Affine2D::from_mat(t)
+    }
+
     #[allow(non_snake_case)]
     fn solve_affine(A: DMatrix<f32>, b: DVector<f32>) -> Option<DVector<f32>> {
         if A.nrows() > A.ncols() {
@@ -29,7 +45,7 @@ impl Affine2D {
                 _ => None,
             }
         } else {
-            Some(A.try_inverse().unwrap() * b)
+            A.try_inverse().map(|m| m * b)
         }
     }
 
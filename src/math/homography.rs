@@ -0,0 +1,213 @@
+extern crate nalgebra;
+
+use nalgebra::{Matrix3, DMatrix, SymmetricEigen};
+use geo::{Point, Pointf};
+use math::affine::Affine2D;
+
+/// Planar projective transform.
+#[derive(Debug, Clone)]
+pub struct Homography {
+    pub t: Matrix3<f32>,
+    pub t_inv: Matrix3<f32>,
+}
+
+// similarity moving the centroid of `pts` to the origin and their mean
+// distance from it to sqrt(2)
+fn normalization(pts: &[Pointf]) -> Matrix3<f64> {
+    let n = pts.len() as f64;
+    let cx = pts.iter().map(|p| p.x as f64).sum::<f64>() / n;
+    let cy = pts.iter().map(|p| p.y as f64).sum::<f64>() / n;
+    let d = pts.iter()
+        .map(|p| ((p.x as f64 - cx).powi(2) + (p.y as f64 - cy).powi(2)).sqrt())
+        .sum::<f64>() / n;
+    let s = if d > 0.0 { 2f64.sqrt() / d } else { 1.0 };
+    Matrix3::new(s, 0.0, -s * cx, 0.0, s, -s * cy, 0.0, 0.0, 1.0)
+}
+
+fn transform(m: &Matrix3<f64>, p: &Pointf) -> (f64, f64) {
+    let (x, y) = (p.x as f64, p.y as f64);
+    let w = m[(2, 0)] * x + m[(2, 1)] * y + m[(2, 2)];
+    (
+        (m[(0, 0)] * x + m[(0, 1)] * y + m[(0, 2)]) / w,
+        (m[(1, 0)] * x + m[(1, 1)] * y + m[(1, 2)]) / w,
+    )
+}
+
+fn project(m: &Matrix3<f32>, p: Pointf) -> Pointf {
+    let w = m[(2, 0)] * p.x + m[(2, 1)] * p.y + m[(2, 2)];
+    Point {
+        x: (m[(0, 0)] * p.x + m[(0, 1)] * p.y + m[(0, 2)]) / w,
+        y: (m[(1, 0)] * p.x + m[(1, 1)] * p.y + m[(1, 2)]) / w,
+    }
+}
+
+// element of `t` with the largest magnitude, which homographies are scaled
+// by; unlike h33 it is only zero for the zero matrix
+fn largest(t: &Matrix3<f32>) -> f32 {
+    t.iter().fold(0f32, |m, &v| if v.abs() > m.abs() { v } else { m })
+}
+
+impl Homography {
+    fn from_mat(t: Matrix3<f32>) -> Option<Homography> {
+        let s = largest(&t);
+        if s == 0f32 || !s.is_finite() {
+            return None;
+        }
+        let t = t / s;
+        match t.try_inverse() {
+            Some(ti) => Some(Homography { t: t, t_inv: ti }),
+            _ => None,
+        }
+    }
+
+    pub fn from_matrix(m: &[[f32; 3]; 3]) -> Option<Homography> {
+        let t: Matrix3<f32> = Matrix3::new(
+            m[0][0],
+            m[0][1],
+            m[0][2],
+            m[1][0],
+            m[1][1],
+            m[1][2],
+            m[2][0],
+            m[2][1],
+            m[2][2],
+        );
+        Homography::from_mat(t)
+    }
+
+    pub fn identity() -> Homography {
+        Homography {
+            t: Matrix3::identity(),
+            t_inv: Matrix3::identity(),
+        }
+    }
+
+    /// Estimates the homography mapping `src` to `dst` from four or more
+    /// correspondences with the normalized direct linear transform.
+    pub fn from_points(src: &[Pointf], dst: &[Pointf]) -> Option<Homography> {
+        if src.len() != dst.len() || src.len() < 4 {
+            return None;
+        }
+        let n = src.len();
+        let ts = normalization(src);
+        let td = normalization(dst);
+
+        let mut a: DMatrix<f64> = DMatrix::from_element(2 * n, 9, 0.0);
+        for i in 0..n {
+            let (x, y) = transform(&ts, &src[i]);
+            let (u, v) = transform(&td, &dst[i]);
+            let r0 = [-x, -y, -1.0, 0.0, 0.0, 0.0, u * x, u * y, u];
+            let r1 = [0.0, 0.0, 0.0, -x, -y, -1.0, v * x, v * y, v];
+            for j in 0..9 {
+                a[(2 * i, j)] = r0[j];
+                a[(2 * i + 1, j)] = r1[j];
+            }
+        }
+        // the solution is the eigenvector of A^T A with the smallest eigenvalue
+        let eig = SymmetricEigen::new(a.transpose() * a);
+        let mut k = 0;
+        for i in 1..9 {
+            if eig.eigenvalues[i] < eig.eigenvalues[k] {
+                k = i;
+            }
+        }
+        let h = eig.eigenvectors.column(k);
+        let hn = Matrix3::new(h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], h[8]);
+        let td_inv = match td.try_inverse() {
+            Some(m) => m,
+            _ => return None,
+        };
+        let m = td_inv * hn * ts;
+        Homography::from_mat(m.map(|v| v as f32))
+    }
+
+    pub fn from_affine(affine: &Affine2D) -> Homography {
+        Homography {
+            t: affine.t,
+            t_inv: affine.t_inv,
+        }
+    }
+
+    pub fn inverse(&self) -> Homography {
+        Homography {
+            t: self.t_inv,
+            t_inv: self.t,
+        }
+    }
+
+    /// Transform applying `other` first, then `self`.
+    pub fn compose(&self, other: &Homography) -> Homography {
+        let t = self.t * other.t;
+        let t_inv = other.t_inv * self.t_inv;
+        let s = largest(&t);
+        Homography {
+            t: t / s,
+            t_inv: t_inv * s,
+        }
+    }
+
+    pub fn map_point(&self, src: Pointf) -> Pointf {
+        project(&self.t, src)
+    }
+
+    pub fn map_point_inv(&self, src: Pointf) -> Pointf {
+        project(&self.t_inv, src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use geo::*;
+    use super::*;
+
+    fn assert_near(a: Pointf, b: Pointf) {
+        assert!((a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_homography() {
+        let src = vec![
+            Pointf::new(0f32, 0f32),
+            Pointf::new(100f32, 0f32),
+            Pointf::new(100f32, 100f32),
+            Pointf::new(0f32, 100f32),
+        ];
+        let dst = vec![
+            Pointf::new(10f32, 20f32),
+            Pointf::new(90f32, 10f32),
+            Pointf::new(120f32, 140f32),
+            Pointf::new(-5f32, 110f32),
+        ];
+        let h = Homography::from_points(&src, &dst).unwrap();
+        for i in 0..4 {
+            assert_near(h.map_point(src[i]), dst[i]);
+            assert_near(h.map_point_inv(dst[i]), src[i]);
+            assert_near(h.inverse().map_point(dst[i]), src[i]);
+        }
+
+        let id = h.compose(&h.inverse());
+        let p = Pointf::new(33f32, 44f32);
+        assert_near(id.map_point(p), p);
+    }
+
+    #[test]
+    fn test_homography_zero_h33() {
+        // swaps x and w, mapping (x, y) to (1 / x, y / x)
+        let m = [[0f32, 0f32, 1f32], [0f32, 1f32, 0f32], [1f32, 0f32, 0f32]];
+        let h = Homography::from_matrix(&m).unwrap();
+        let (p, q) = (Pointf::new(2f32, 4f32), Pointf::new(0.5f32, 2f32));
+        assert_near(h.map_point(p), q);
+        assert_near(h.map_point_inv(q), p);
+        let c = h.compose(&Homography::identity());
+        assert!(c.t.iter().chain(c.t_inv.iter()).all(|v| v.is_finite()));
+        assert_near(c.map_point(p), q);
+        assert!(Homography::from_matrix(&[[0f32; 3]; 3]).is_none());
+    }
+
+    #[test]
+    fn test_homography_too_few_points() {
+        let src = vec![Pointf::new(0f32, 0f32); 4];
+        assert!(Homography::from_points(&src, &src[..3]).is_none());
+        assert!(Homography::from_points(&src[..3], &src[..3]).is_none());
+    }
+}
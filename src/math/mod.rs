@@ -1,2 +1,4 @@
 pub mod affine;
+pub mod homography;
+pub mod robust;
 pub mod utils;
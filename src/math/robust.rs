@@ -0,0 +1,462 @@
+//! Robust model estimation from point correspondences containing outliers.
+
+use geo::Pointf;
+use math::affine::Affine2D;
+use math::homography::Homography;
+use math::utils::XorShiftRng;
+
+/// A geometric model that can be fitted to point correspondences.
+pub trait Model: Sized {
+    /// Number of correspondences needed to fit a model.
+    fn min_samples() -> usize;
+
+    /// Least-squares fit to the correspondences.
+    fn fit(src: &[Pointf], dst: &[Pointf]) -> Option<Self>;
+
+    /// Reprojection error of one correspondence.
+    fn error(&self, src: Pointf, dst: Pointf) -> f32;
+}
+
+#[inline]
+fn dist(a: Pointf, b: Pointf) -> f32 {
+    ((a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y)).sqrt()
+}
+
+impl Model for Affine2D {
+    fn min_samples() -> usize {
+        3
+    }
+
+    fn fit(src: &[Pointf], dst: &[Pointf]) -> Option<Self> {
+        Affine2D::affine_from_points(src, dst)
+    }
+
+    fn error(&self, src: Pointf, dst: Pointf) -> f32 {
+        dist(self.map_point(src), dst)
+    }
+}
+
+/// Non-reflective similarity (rotation, uniform scale and translation).
+#[derive(Debug, Clone)]
+pub struct Similarity(pub Affine2D);
+
+impl Model for Similarity {
+    fn min_samples() -> usize {
+        2
+    }
+
+    fn fit(src: &[Pointf], dst: &[Pointf]) -> Option<Self> {
+        Affine2D::nonreflect_similarity_from_points(src, dst).map(Similarity)
+    }
+
+    fn error(&self, src: Pointf, dst: Pointf) -> f32 {
+        dist(self.0.map_point(src), dst)
+    }
+}
+
+impl Model for Homography {
+    fn min_samples() -> usize {
+        4
+    }
+
+    fn fit(src: &[Pointf], dst: &[Pointf]) -> Option<Self> {
+        Homography::from_points(src, dst)
+    }
+
+    fn error(&self, src: Pointf, dst: Pointf) -> f32 {
+        dist(self.map_point(src), dst)
+    }
+}
+
+/// Result of a robust estimation.
+#[derive(Debug, Clone)]
+pub struct Estimate<M> {
+    pub model: M,
+    /// `inliers[i]` tells whether correspondence `i` agrees with `model`
+    pub inliers: Vec<bool>,
+    /// root mean square reprojection error over the inliers
+    pub error: f32,
+}
+
+pub trait RobustEstimator {
+    fn estimate<M: Model>(&self, src: &[Pointf], dst: &[Pointf]) -> Option<Estimate<M>>;
+}
+
+fn sample_distinct(rng: &mut XorShiftRng, n: usize, k: usize, out: &mut Vec<usize>) {
+    out.clear();
+    while out.len() < k {
+        let i = rng.gen_range(n);
+        if !out.contains(&i) {
+            out.push(i);
+        }
+    }
+}
+
+fn fit_subset<M: Model>(src: &[Pointf], dst: &[Pointf], idx: &[usize]) -> Option<M> {
+    let s: Vec<Pointf> = idx.iter().map(|&i| src[i]).collect();
+    let d: Vec<Pointf> = idx.iter().map(|&i| dst[i]).collect();
+    M::fit(&s, &d)
+}
+
+fn find_inliers<M: Model>(model: &M, src: &[Pointf], dst: &[Pointf], threshold: f32) -> Vec<bool> {
+    src.iter()
+        .zip(dst.iter())
+        .map(|(&s, &d)| model.error(s, d) <= threshold)
+        .collect()
+}
+
+// refits `model` on its inliers, keeping the refined model only if it does
+// not lose support
+fn refine<M: Model>(model: M, src: &[Pointf], dst: &[Pointf], threshold: f32) -> Estimate<M> {
+    let mut model = model;
+    let mut inliers = find_inliers(&model, src, dst, threshold);
+    let count = |m: &[bool]| m.iter().filter(|&&b| b).count();
+    let idx: Vec<usize> = (0..src.len()).filter(|&i| inliers[i]).collect();
+    if idx.len() >= M::min_samples() {
+        if let Some(m) = fit_subset::<M>(src, dst, &idx) {
+            let refined = find_inliers(&m, src, dst, threshold);
+            if count(&refined) >= count(&inliers) {
+                model = m;
+                inliers = refined;
+            }
+        }
+    }
+    let mut sum = 0f32;
+    let mut n = 0;
+    for i in 0..src.len() {
+        if inliers[i] {
+            let e = model.error(src[i], dst[i]);
+            sum += e * e;
+            n += 1;
+        }
+    }
+    let error = if n > 0 { (sum / n as f32).sqrt() } else { 0f32 };
+    Estimate {
+        model: model,
+        inliers: inliers,
+        error: error,
+    }
+}
+
+// number of iterations needed to draw an all-inlier sample with probability
+// `confidence`, given the inlier ratio
+fn required_iters(confidence: f32, inlier_ratio: f32, samples: usize, max_iters: usize) -> usize {
+    let good = inlier_ratio.powi(samples as i32);
+    if good <= 0f32 {
+        return max_iters;
+    }
+    if good >= 1f32 {
+        return 1;
+    }
+    let n = (1f32 - confidence).ln() / (1f32 - good).ln();
+    if n.is_finite() && n < max_iters as f32 {
+        n.ceil() as usize
+    } else {
+        max_iters
+    }
+}
+
+/// Random sample consensus (Fischler and Bolles).
+#[derive(Debug, Clone)]
+pub struct Ransac {
+    threshold: f32,
+    confidence: f32,
+    max_iters: usize,
+    seed: u64,
+}
+
+impl Ransac {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum reprojection error of an inlier.
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn max_iters(mut self, max_iters: usize) -> Self {
+        self.max_iters = max_iters;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl Default for Ransac {
+    fn default() -> Self {
+        Ransac {
+            threshold: 3f32,
+            confidence: 0.99,
+            max_iters: 2000,
+            seed: 0,
+        }
+    }
+}
+
+impl RobustEstimator for Ransac {
+    fn estimate<M: Model>(&self, src: &[Pointf], dst: &[Pointf]) -> Option<Estimate<M>> {
+        let n = src.len();
+        let m = M::min_samples();
+        if n != dst.len() || n < m {
+            return None;
+        }
+        let mut rng = XorShiftRng::new(self.seed);
+        let mut sample = Vec::with_capacity(m);
+        let mut best: Option<(usize, M)> = None;
+        let mut iters = self.max_iters;
+        let mut i = 0;
+        while i < iters {
+            i += 1;
+            sample_distinct(&mut rng, n, m, &mut sample);
+            let model = match fit_subset::<M>(src, dst, &sample) {
+                Some(model) => model,
+                None => continue,
+            };
+            let support = find_inliers(&model, src, dst, self.threshold)
+                .iter()
+                .filter(|&&b| b)
+                .count();
+            if best.as_ref().map_or(true, |&(s, _)| support > s) {
+                let ratio = support as f32 / n as f32;
+                iters = required_iters(self.confidence, ratio, m, self.max_iters);
+                best = Some((support, model));
+            }
+        }
+        best.map(|(_, model)| refine(model, src, dst, self.threshold))
+    }
+}
+
+/// Progressive sample consensus (Chum and Matas). The correspondences must be
+/// sorted by decreasing quality, e.g. by descriptor match score.
+#[derive(Debug, Clone)]
+pub struct Prosac {
+    ransac: Ransac,
+}
+
+impl Prosac {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.ransac = self.ransac.threshold(threshold);
+        self
+    }
+
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.ransac = self.ransac.confidence(confidence);
+        self
+    }
+
+    pub fn max_iters(mut self, max_iters: usize) -> Self {
+        self.ransac = self.ransac.max_iters(max_iters);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.ransac = self.ransac.seed(seed);
+        self
+    }
+}
+
+impl Default for Prosac {
+    fn default() -> Self {
+        Prosac { ransac: Ransac::default() }
+    }
+}
+
+impl RobustEstimator for Prosac {
+    fn estimate<M: Model>(&self, src: &[Pointf], dst: &[Pointf]) -> Option<Estimate<M>> {
+        let params = &self.ransac;
+        let n = src.len();
+        let m = M::min_samples();
+        if n != dst.len() || n < m {
+            return None;
+        }
+        let mut rng = XorShiftRng::new(params.seed);
+        let mut sample = Vec::with_capacity(m);
+        let mut best: Option<(usize, M)> = None;
+
+        // growth function of the sampling pool, see section 2.3 of the paper
+        let mut pool = m;
+        let mut t_n = params.max_iters as f32;
+        for i in 0..m {
+            t_n *= (m - i) as f32 / (n - i) as f32;
+        }
+        let mut t_n_prime = 1usize;
+
+        let mut iters = params.max_iters;
+        let mut t = 0;
+        while t < iters {
+            t += 1;
+            while t_n_prime <= t && pool < n {
+                let t_next = t_n * (pool + 1) as f32 / (pool + 1 - m) as f32;
+                t_n_prime += (t_next - t_n).ceil().max(0f32) as usize;
+                t_n = t_next;
+                pool += 1;
+            }
+            if t_n_prime < t || pool == m {
+                sample_distinct(&mut rng, pool, m, &mut sample);
+            } else {
+                // the newest correspondence plus m - 1 drawn from the rest
+                sample_distinct(&mut rng, pool - 1, m - 1, &mut sample);
+                sample.push(pool - 1);
+            }
+            let model = match fit_subset::<M>(src, dst, &sample) {
+                Some(model) => model,
+                None => continue,
+            };
+            let support = find_inliers(&model, src, dst, params.threshold)
+                .iter()
+                .filter(|&&b| b)
+                .count();
+            if best.as_ref().map_or(true, |&(s, _)| support > s) {
+                iters = required_iters(
+                    params.confidence,
+                    support as f32 / n as f32,
+                    m,
+                    params.max_iters,
+                );
+                best = Some((support, model));
+            }
+        }
+        best.map(|(_, model)| refine(model, src, dst, params.threshold))
+    }
+}
+
+/// Least median of squares (Rousseeuw). Needs no inlier threshold but
+/// tolerates at most 50% outliers.
+#[derive(Debug, Clone)]
+pub struct LMedS {
+    confidence: f32,
+    max_iters: usize,
+    seed: u64,
+}
+
+impl LMedS {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn confidence(mut self, confidence: f32) -> Self {
+        self.confidence = confidence;
+        self
+    }
+
+    pub fn max_iters(mut self, max_iters: usize) -> Self {
+        self.max_iters = max_iters;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+impl Default for LMedS {
+    fn default() -> Self {
+        LMedS {
+            confidence: 0.99,
+            max_iters: 2000,
+            seed: 0,
+        }
+    }
+}
+
+impl RobustEstimator for LMedS {
+    fn estimate<M: Model>(&self, src: &[Pointf], dst: &[Pointf]) -> Option<Estimate<M>> {
+        let n = src.len();
+        let m = M::min_samples();
+        if n != dst.len() || n < m {
+            return None;
+        }
+        let mut rng = XorShiftRng::new(self.seed);
+        let mut sample = Vec::with_capacity(m);
+        let mut errors = vec![0f32; n];
+        let mut best: Option<(f32, M)> = None;
+        let iters = required_iters(self.confidence, 0.5, m, self.max_iters);
+        for _ in 0..iters {
+            sample_distinct(&mut rng, n, m, &mut sample);
+            let model = match fit_subset::<M>(src, dst, &sample) {
+                Some(model) => model,
+                None => continue,
+            };
+            for i in 0..n {
+                let e = model.error(src[i], dst[i]);
+                errors[i] = if e.is_finite() { e * e } else { ::std::f32::MAX };
+            }
+            errors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let median = errors[n / 2];
+            if best.as_ref().map_or(true, |&(b, _)| median < b) {
+                best = Some((median, model));
+            }
+        }
+        best.map(|(median, model)| {
+            // robust standard deviation estimate
+            let sigma = 1.4826 * (1f32 + 5f32 / (n as f32 - m as f32).max(1f32)) * median.sqrt();
+            refine(model, src, dst, (2.5 * sigma).max(1e-3))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn correspondences() -> (Vec<Pointf>, Vec<Pointf>) {
+        let mut src = Vec::new();
+        let mut dst = Vec::new();
+        for i in 0..30 {
+            let p = Pointf::new((i % 6) as f32 * 10f32, (i / 6) as f32 * 7f32);
+            src.push(p);
+            // rotation by 90 degrees, scale 2, translation (5, -3)
+            dst.push(Pointf::new(-2f32 * p.y + 5f32, 2f32 * p.x - 3f32));
+        }
+        // corrupt a third of them
+        for i in (0..30).filter(|i| i % 3 == 1) {
+            dst[i] = Pointf::new(dst[i].x + 40f32 + i as f32, dst[i].y - 25f32);
+        }
+        (src, dst)
+    }
+
+    fn check<M: Model>(est: Option<Estimate<M>>) {
+        let est = est.unwrap();
+        for i in 0..30 {
+            assert_eq!(est.inliers[i], i % 3 != 1);
+        }
+        assert!(est.error < 1e-2);
+    }
+
+    #[test]
+    fn test_ransac() {
+        let (src, dst) = correspondences();
+        let ransac = Ransac::new().threshold(1f32).seed(7);
+        check::<Similarity>(ransac.estimate(&src, &dst));
+        check::<Affine2D>(ransac.estimate(&src, &dst));
+        check::<Homography>(ransac.estimate(&src, &dst));
+    }
+
+    #[test]
+    fn test_prosac() {
+        let (src, dst) = correspondences();
+        check::<Affine2D>(Prosac::new().threshold(1f32).estimate(&src, &dst));
+    }
+
+    #[test]
+    fn test_lmeds() {
+        let (src, dst) = correspondences();
+        check::<Similarity>(LMedS::new().estimate(&src, &dst));
+        check::<Homography>(LMedS::new().estimate(&src, &dst));
+    }
+}
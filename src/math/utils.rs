@@ -23,6 +23,12 @@ pub fn clip<T: Primitive>(x: T, min: T, max: T) -> T {
     x
 }
 
+/// Whether `T` is an integer type, which cannot hold 0.5.
+#[inline]
+pub fn is_integral<T: Primitive>() -> bool {
+    T::from(0.5f32).map_or(true, |h: T| h == T::zero())
+}
+
 pub fn clip_from_f32<T: Primitive>(x: f32, min: T, max: T) -> T {
     let min = min.to_f32().unwrap();
     let max = max.to_f32().unwrap();
@@ -35,3 +41,36 @@ pub fn clip_from_f32<T: Primitive>(x: f32, min: T, max: T) -> T {
     };
     T::from(ret).unwrap()
 }
+
+/// Small deterministic pseudo random generator (xorshift64*), good enough
+/// for sampling in the estimation and clustering routines.
+#[derive(Debug, Clone)]
+pub struct XorShiftRng {
+    state: u64,
+}
+
+impl XorShiftRng {
+    pub fn new(seed: u64) -> XorShiftRng {
+        // the state must never be zero
+        XorShiftRng { state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform integer in `0..n`.
+    pub fn gen_range(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
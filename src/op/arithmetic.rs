@@ -0,0 +1,210 @@
+use std::ops::{Add, Sub};
+use image::{Image, ImageBinary, ImageError};
+use pixel::{Pixel, gray};
+use sampler::saturate;
+use num::ToPrimitive;
+
+/// Comparison used by `compare`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CmpOp {
+    fn test(&self, a: f32, b: f32) -> bool {
+        match *self {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+        }
+    }
+}
+
+fn check_size<P: Pixel, Q: Pixel>(a: &Image<P>, b: &Image<Q>) -> Result<(), ImageError> {
+    if a.size() == b.size() {
+        Ok(())
+    } else {
+        Err(ImageError::SizeMismatch)
+    }
+}
+
+// applies `f` to every pair of channels, saturating the result
+fn zip_channels<P, F>(a: &Image<P>, b: &Image<P>, f: F) -> Result<Image<P>, ImageError>
+where
+    P: Pixel,
+    F: Fn(f32, f32) -> f32,
+{
+    check_size(a, b)?;
+    Ok(a.zip_map(b, |pa, pb| {
+        let mut p = P::zero();
+        for c in 0..P::channels() {
+            let va = pa.raw()[c].to_f32().unwrap();
+            let vb = pb.raw()[c].to_f32().unwrap();
+            p.raw_mut()[c] = saturate(f(va, vb));
+        }
+        p
+    }))
+}
+
+fn map_channels<P, F>(a: &Image<P>, f: F) -> Image<P>
+where
+    P: Pixel,
+    F: Fn(f32) -> f32,
+{
+    a.map(|pa| {
+        let mut p = P::zero();
+        for c in 0..P::channels() {
+            p.raw_mut()[c] = saturate(f(pa.raw()[c].to_f32().unwrap()));
+        }
+        p
+    })
+}
+
+/// Saturating `a + b`.
+pub fn add<P: Pixel>(a: &Image<P>, b: &Image<P>) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, |x, y| x + y)
+}
+
+/// Saturating `a - b`.
+pub fn sub<P: Pixel>(a: &Image<P>, b: &Image<P>) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, |x, y| x - y)
+}
+
+/// `|a - b|`
+pub fn absdiff<P: Pixel>(a: &Image<P>, b: &Image<P>) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, |x, y| (x - y).abs())
+}
+
+/// Saturating `a * b * scale`.
+pub fn mul<P: Pixel>(a: &Image<P>, b: &Image<P>, scale: f32) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, |x, y| x * y * scale)
+}
+
+/// Saturating `a * scale / b`, 0 where `b` is 0.
+pub fn div<P: Pixel>(a: &Image<P>, b: &Image<P>, scale: f32) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, |x, y| if y == 0f32 { 0f32 } else { x * scale / y })
+}
+
+/// Saturating `a * alpha + b * beta + gamma`.
+pub fn add_weighted<P: Pixel>(
+    a: &Image<P>,
+    alpha: f32,
+    b: &Image<P>,
+    beta: f32,
+    gamma: f32,
+) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, |x, y| x * alpha + y * beta + gamma)
+}
+
+/// Per channel minimum.
+pub fn min<P: Pixel>(a: &Image<P>, b: &Image<P>) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, f32::min)
+}
+
+/// Per channel maximum.
+pub fn max<P: Pixel>(a: &Image<P>, b: &Image<P>) -> Result<Image<P>, ImageError> {
+    zip_channels(a, b, f32::max)
+}
+
+/// Saturating `a + s` on every channel.
+pub fn add_scalar<P: Pixel>(a: &Image<P>, s: f32) -> Image<P> {
+    map_channels(a, |x| x + s)
+}
+
+/// Saturating `a - s` on every channel.
+pub fn sub_scalar<P: Pixel>(a: &Image<P>, s: f32) -> Image<P> {
+    map_channels(a, |x| x - s)
+}
+
+/// Saturating `a * s` on every channel.
+pub fn mul_scalar<P: Pixel>(a: &Image<P>, s: f32) -> Image<P> {
+    map_channels(a, |x| x * s)
+}
+
+/// Saturating `a / s` on every channel, 0 if `s` is 0.
+pub fn div_scalar<P: Pixel>(a: &Image<P>, s: f32) -> Image<P> {
+    map_channels(a, |x| if s == 0f32 { 0f32 } else { x / s })
+}
+
+/// Sets the pixels where `op` holds between `a` and `b` in every channel.
+pub fn compare<P: Pixel>(
+    a: &Image<P>,
+    b: &Image<P>,
+    op: CmpOp,
+) -> Result<ImageBinary, ImageError> {
+    check_size(a, b)?;
+    let mut dst = ImageBinary::new(a.width(), a.height());
+    for (x, y, p) in dst.iter_mut() {
+        let (pa, pb) = (a[(x, y)], b[(x, y)]);
+        let hold = pa.raw().iter().zip(pb.raw()).all(|(va, vb)| {
+            op.test(va.to_f32().unwrap(), vb.to_f32().unwrap())
+        });
+        *p = gray(hold as u8);
+    }
+    Ok(dst)
+}
+
+/// Saturating addition, panicking if the sizes differ; see `add`.
+impl<P: Pixel> Add for Image<P> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        add(&self, &rhs).expect("images differ in size")
+    }
+}
+
+/// Saturating subtraction, panicking if the sizes differ; see `sub`.
+impl<P: Pixel> Sub for Image<P> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        sub(&self, &rhs).expect("images differ in size")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageGrayf;
+    use pixel::Gray;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = gray_image![10, 200, 30; 0, 128, 255];
+        let b = gray_image![20, 100, 30; 5, 128, 0];
+        assert_eq!(add(&a, &b).unwrap(), gray_image![30, 255, 60; 5, 255, 255]);
+        assert_eq!(sub(&a, &b).unwrap(), gray_image![0, 100, 0; 0, 0, 255]);
+        assert_eq!(absdiff(&a, &b).unwrap(), gray_image![10, 100, 0; 5, 0, 255]);
+        assert_eq!(mul(&a, &b, 1f32 / 255f32).unwrap()[(1, 0)], gray(78));
+        assert_eq!(div(&a, &b, 2f32).unwrap(), gray_image![1, 4, 2; 0, 2, 0]);
+        assert_eq!(
+            add_weighted(&a, 0.5, &b, 0.5, 1f32).unwrap(),
+            gray_image![16, 151, 31; 4, 129, 129]
+        );
+        assert_eq!(min(&a, &b).unwrap(), gray_image![10, 100, 30; 0, 128, 0]);
+        assert_eq!(max(&a, &b).unwrap(), gray_image![20, 200, 30; 5, 128, 255]);
+        assert_eq!(sub_scalar(&a, 20f32), gray_image![0, 180, 10; 0, 108, 235]);
+        assert_eq!(a.clone() + b.clone(), add(&a, &b).unwrap());
+        assert_eq!(a.clone() - b.clone(), sub(&a, &b).unwrap());
+
+        let gt = compare(&a, &b, CmpOp::Gt).unwrap();
+        assert_eq!(gt, gray_image![0, 1, 0; 0, 0, 1]);
+
+        let small = gray_image![1, 2];
+        match add(&a, &small) {
+            Err(ImageError::SizeMismatch) => {}
+            other => panic!("{:?}", other),
+        }
+
+        // float images do not saturate at 255
+        let mut f = ImageGrayf::new(1, 1);
+        f[(0, 0)] = Gray([200f32]);
+        assert_eq!(add(&f, &f).unwrap()[(0, 0)], Gray([400f32]));
+    }
+}
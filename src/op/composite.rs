@@ -0,0 +1,265 @@
+use image::{Image, ImageGray};
+use pixel::{Pixel, AlphaPixel, full_scale};
+use geo::Pointi;
+use sampler::saturate;
+use num::ToPrimitive;
+
+/// Porter-Duff compositing operators (Porter and Duff, 1984), placing a
+/// source over a destination.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompositeOp {
+    /// source over destination
+    Over,
+    /// source where the destination is
+    In,
+    /// source where the destination is not
+    Out,
+    /// source over destination, kept where the destination is
+    Atop,
+    /// source and destination where the other is not
+    Xor,
+}
+
+impl CompositeOp {
+    // fractions of source and destination kept, given their alphas
+    fn factors(&self, sa: f32, da: f32) -> (f32, f32) {
+        match *self {
+            CompositeOp::Over => (1f32, 1f32 - sa),
+            CompositeOp::In => (da, 0f32),
+            CompositeOp::Out => (1f32 - da, 0f32),
+            CompositeOp::Atop => (da, 1f32 - sa),
+            CompositeOp::Xor => (1f32 - da, 1f32 - sa),
+        }
+    }
+}
+
+/// Separable blend modes as in the W3C compositing specification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    Multiply,
+    Screen,
+    Overlay,
+    SoftLight,
+    Difference,
+}
+
+impl BlendMode {
+    /// Blends backdrop `b` with source `s`, both in `[0, 1]`.
+    pub fn apply(&self, b: f32, s: f32) -> f32 {
+        match *self {
+            BlendMode::Multiply => b * s,
+            BlendMode::Screen => b + s - b * s,
+            BlendMode::Overlay => {
+                if b <= 0.5 {
+                    2f32 * b * s
+                } else {
+                    1f32 - 2f32 * (1f32 - b) * (1f32 - s)
+                }
+            }
+            BlendMode::SoftLight => {
+                if s <= 0.5 {
+                    b - (1f32 - 2f32 * s) * b * (1f32 - b)
+                } else {
+                    let d = if b <= 0.25 {
+                        ((16f32 * b - 12f32) * b + 4f32) * b
+                    } else {
+                        b.sqrt()
+                    };
+                    b + (2f32 * s - 1f32) * (d - b)
+                }
+            }
+            BlendMode::Difference => (b - s).abs(),
+        }
+    }
+}
+
+#[inline]
+fn channel<P: Pixel>(p: &P, c: usize) -> f32 {
+    p.raw()[c].to_f32().unwrap()
+}
+
+/// Multiplies the color channels of `img` by alpha.
+pub fn premultiply<P: AlphaPixel>(img: &mut Image<P>) {
+    let ai = P::alpha_index();
+    let opaque = P::opaque();
+    for (_, _, p) in img.iter_mut() {
+        let a = channel(p, ai) / opaque;
+        for c in (0..P::channels()).filter(|&c| c != ai) {
+            let v = channel(p, c) * a;
+            p.raw_mut()[c] = saturate(v);
+        }
+    }
+}
+
+/// Divides the color channels of `img` by alpha, the inverse of
+/// `premultiply` up to rounding. Fully transparent pixels become zero.
+pub fn unpremultiply<P: AlphaPixel>(img: &mut Image<P>) {
+    let ai = P::alpha_index();
+    let opaque = P::opaque();
+    for (_, _, p) in img.iter_mut() {
+        let a = channel(p, ai) / opaque;
+        for c in (0..P::channels()).filter(|&c| c != ai) {
+            let v = if a > 0f32 { channel(p, c) / a } else { 0f32 };
+            p.raw_mut()[c] = saturate(v);
+        }
+    }
+}
+
+// composites straight alpha pixels, with `mode` applied to the colors where
+// both overlap
+fn composite_pixel<P: AlphaPixel>(s: &P, d: &P, op: CompositeOp, mode: Option<BlendMode>) -> P {
+    let ai = P::alpha_index();
+    let opaque = P::opaque();
+    let sa = channel(s, ai) / opaque;
+    let da = channel(d, ai) / opaque;
+    let (fs, fd) = op.factors(sa, da);
+    let oa = fs * sa + fd * da;
+    let mut out = P::zero();
+    if oa <= 0f32 {
+        return out;
+    }
+    for c in 0..P::channels() {
+        out.raw_mut()[c] = if c == ai {
+            saturate(oa * opaque)
+        } else {
+            let sc = channel(s, c) / opaque;
+            let dc = channel(d, c) / opaque;
+            let sc = match mode {
+                Some(m) => (1f32 - da) * sc + da * m.apply(dc, sc),
+                None => sc,
+            };
+            saturate((fs * sa * sc + fd * da * dc) / oa * opaque)
+        };
+    }
+    out
+}
+
+/// Composites `src` onto `dst` with `op`. Both images have straight alpha
+/// and the same size.
+pub fn composite<P: AlphaPixel>(src: &Image<P>, dst: &Image<P>, op: CompositeOp) -> Image<P> {
+    assert_eq!(src.size(), dst.size());
+    let mut out = Image::new(dst.width(), dst.height());
+    for (x, y, p) in out.iter_mut() {
+        *p = composite_pixel(&src[(x, y)], &dst[(x, y)], op, None);
+    }
+    out
+}
+
+/// Blends the colors of `src` with `dst` by `mode` and composites the result
+/// over `dst`, weighted by the alphas of both.
+pub fn blend_over<P: AlphaPixel>(src: &Image<P>, dst: &Image<P>, mode: BlendMode) -> Image<P> {
+    assert_eq!(src.size(), dst.size());
+    let mut out = Image::new(dst.width(), dst.height());
+    for (x, y, p) in out.iter_mut() {
+        *p = composite_pixel(&src[(x, y)], &dst[(x, y)], CompositeOp::Over, Some(mode));
+    }
+    out
+}
+
+/// Applies `mode` to every channel of the opaque images `backdrop` and
+/// `src`.
+pub fn blend<P: Pixel>(backdrop: &Image<P>, src: &Image<P>, mode: BlendMode) -> Image<P> {
+    assert_eq!(src.size(), backdrop.size());
+    let unit = full_scale::<P::Subpixel>();
+    let mut out: Image<P> = Image::new(src.width(), src.height());
+    for (x, y, p) in out.iter_mut() {
+        let (b, s) = (&backdrop[(x, y)], &src[(x, y)]);
+        for c in 0..P::channels() {
+            let v = mode.apply(channel(b, c) / unit, channel(s, c) / unit);
+            p.raw_mut()[c] = saturate(v * unit);
+        }
+    }
+    out
+}
+
+/// Copies `src` into `dst` with its top left corner at `at`, clipped to
+/// `dst`. A `mask` the size of `src` weights every source pixel by
+/// `mask / 255`.
+pub fn paste<P: Pixel>(dst: &mut Image<P>, src: &Image<P>, at: Pointi, mask: Option<&ImageGray>) {
+    if let Some(m) = mask {
+        assert_eq!(m.size(), src.size());
+    }
+    let (sw, sh) = (src.width() as isize, src.height() as isize);
+    let (dw, dh) = (dst.width() as isize, dst.height() as isize);
+    let (x0, y0) = ((-at.x).max(0), (-at.y).max(0));
+    let (x1, y1) = (sw.min(dw - at.x), sh.min(dh - at.y));
+    for sy in y0..y1 {
+        for sx in x0..x1 {
+            let s = src[(sx as usize, sy as usize)];
+            let d = &mut dst[((sx + at.x) as usize, (sy + at.y) as usize)];
+            match mask.map(|m| m[(sx as usize, sy as usize)][0]) {
+                None | Some(255) => *d = s,
+                Some(0) => {}
+                Some(w) => {
+                    let w = w as f32 / 255f32;
+                    for c in 0..P::channels() {
+                        let v = channel(&s, c) * w + channel(d, c) * (1f32 - w);
+                        d.raw_mut()[c] = saturate(v);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageBGRA;
+    use pixel::{BGRA, bgra, gray};
+    use geo::Point;
+
+    fn single(p: BGRA<u8>) -> ImageBGRA {
+        let mut img = ImageBGRA::new(1, 1);
+        img[(0, 0)] = p;
+        img
+    }
+
+    #[test]
+    fn test_composite() {
+        let src = single(bgra(0, 0, 255, 128));
+        let dst = single(bgra(255, 0, 0, 255));
+        let over = composite(&src, &dst, CompositeOp::Over);
+        assert_eq!(over[(0, 0)], bgra(127, 0, 128, 255));
+        let atop = composite(&src, &dst, CompositeOp::Atop);
+        assert_eq!(atop[(0, 0)], over[(0, 0)]);
+        let in_ = composite(&src, &dst, CompositeOp::In);
+        assert_eq!(in_[(0, 0)], bgra(0, 0, 255, 128));
+        assert_eq!(composite(&src, &dst, CompositeOp::Out)[(0, 0)][3], 0);
+        let xor = composite(&src, &dst, CompositeOp::Xor);
+        assert_eq!(xor[(0, 0)], bgra(255, 0, 0, 127));
+
+        let mut img = single(bgra(200, 100, 50, 51));
+        premultiply(&mut img);
+        assert_eq!(img[(0, 0)], bgra(40, 20, 10, 51));
+        unpremultiply(&mut img);
+        assert_eq!(img[(0, 0)], bgra(200, 100, 50, 51));
+
+        // multiply with an opaque white source leaves the backdrop
+        let white = single(bgra(255, 255, 255, 255));
+        let b = blend_over(&white, &dst, BlendMode::Multiply);
+        assert_eq!(b[(0, 0)], dst[(0, 0)]);
+    }
+
+    #[test]
+    fn test_blend_and_paste() {
+        let a = gray_image![0, 64, 128, 255];
+        let b = gray_image![255, 128, 128, 0];
+        assert_eq!(blend(&a, &b, BlendMode::Multiply), gray_image![0, 32, 64, 0]);
+        assert_eq!(blend(&a, &b, BlendMode::Screen), gray_image![255, 160, 192, 255]);
+        assert_eq!(blend(&a, &b, BlendMode::Difference), gray_image![255, 64, 0, 255]);
+        let overlay = blend(&a, &b, BlendMode::Overlay);
+        assert_eq!(overlay[(1, 0)], gray(64));
+        let soft = blend(&a, &b, BlendMode::SoftLight);
+        assert_eq!(soft[(2, 0)], gray(128));
+
+        let mut dst = ImageGray::new(4, 3);
+        dst.zero();
+        let src = gray_image![1, 2; 3, 4];
+        paste(&mut dst, &src, Point::new(-1, 2), None);
+        assert_eq!(dst, gray_image![0, 0, 0, 0; 0, 0, 0, 0; 2, 0, 0, 0]);
+        let mask = gray_image![255, 0; 128, 255];
+        paste(&mut dst, &src, Point::new(2, 0), Some(&mask));
+        assert_eq!(dst, gray_image![0, 0, 1, 0; 0, 0, 2, 4; 2, 0, 0, 0]);
+    }
+}
@@ -1,7 +1,9 @@
 
 use op::filter::{Filter, GeneralKernel};
-use image::Image;
-use pixel::Pixel;
+use image::{Image, ImageGrayf};
+use pixel::{Pixel, Gray, gray};
+use traits::Primitive;
+use eye::Eye;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Sobel {}
@@ -34,6 +36,27 @@ impl Filter for Sobel {
     }
 }
 
+/// Horizontal and vertical 3x3 Sobel derivatives of `img`, unscaled and
+/// with replicated borders.
+pub fn sobel_gradients<T: Primitive>(img: &Image<Gray<T>>) -> (ImageGrayf, ImageGrayf) {
+    let (width, height) = img.size();
+    let mut gx = ImageGrayf::new(width, height);
+    let mut gy = ImageGrayf::new(width, height);
+    let eye = Eye::default().extend();
+    let at = |x: isize, y: isize| eye.x(x).y(y).look(img)[0].to_f32().unwrap();
+    for y in 0..height as isize {
+        for x in 0..width as isize {
+            let (tl, t, tr) = (at(x - 1, y - 1), at(x, y - 1), at(x + 1, y - 1));
+            let (l, r) = (at(x - 1, y), at(x + 1, y));
+            let (bl, b, br) = (at(x - 1, y + 1), at(x, y + 1), at(x + 1, y + 1));
+            let pos = (x as usize, y as usize);
+            gx[pos] = gray(tr + 2f32 * r + br - tl - 2f32 * l - bl);
+            gy[pos] = gray(bl + 2f32 * b + br - tl - 2f32 * t - tr);
+        }
+    }
+    (gx, gy)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -51,4 +74,17 @@ mod test {
         FreeImageIO::save(&path, &result).unwrap();
     }
 
+    #[test]
+    fn test_sobel_gradients() {
+        let img =
+            gray_image![
+            0, 0, 10, 10;
+            0, 0, 10, 10;
+            0, 0, 10, 10
+        ];
+        let (gx, gy) = sobel_gradients(&img);
+        assert_eq!(gx[(1, 1)], gray(40f32));
+        assert_eq!(gx[(0, 1)], gray(0f32));
+        assert_eq!(gy[(2, 1)], gray(0f32));
+    }
 }
@@ -0,0 +1,81 @@
+use image::Image;
+use pixel::Pixel;
+use geo::Recti;
+use num::ToPrimitive;
+
+/// Summed-area table of one channel, giving the sum over any rectangle in
+/// constant time.
+#[derive(Debug, Clone)]
+pub struct IntegralImage {
+    width: usize,
+    height: usize,
+    // (width + 1) x (height + 1), with a zero first row and column
+    data: Vec<f64>,
+}
+
+impl IntegralImage {
+    /// Sums of channel `ch` of `src`.
+    pub fn new<P: Pixel>(src: &Image<P>, ch: usize) -> IntegralImage {
+        IntegralImage::build(src, ch, |v| v)
+    }
+
+    /// Sums of the squares of channel `ch` of `src`.
+    pub fn squared<P: Pixel>(src: &Image<P>, ch: usize) -> IntegralImage {
+        IntegralImage::build(src, ch, |v| v * v)
+    }
+
+    fn build<P: Pixel, F: Fn(f64) -> f64>(src: &Image<P>, ch: usize, f: F) -> IntegralImage {
+        assert!(ch < P::channels());
+        let (width, height) = src.size();
+        let stride = width + 1;
+        let mut data = vec![0f64; stride * (height + 1)];
+        for y in 0..height {
+            let row = src.row(y);
+            let mut acc = 0f64;
+            for x in 0..width {
+                acc += f(row[x].raw()[ch].to_f64().unwrap());
+                data[(y + 1) * stride + x + 1] = data[y * stride + x + 1] + acc;
+            }
+        }
+        IntegralImage {
+            width: width,
+            height: height,
+            data: data,
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Sum over `rect`, which must lie inside the image.
+    pub fn sum(&self, rect: Recti) -> f64 {
+        let (x0, y0) = (rect.x as usize, rect.y as usize);
+        let (x1, y1) = (x0 + rect.width as usize, y0 + rect.height as usize);
+        assert!(x1 <= self.width && y1 <= self.height);
+        let stride = self.width + 1;
+        self.data[y1 * stride + x1] - self.data[y0 * stride + x1] - self.data[y1 * stride + x0] +
+            self.data[y0 * stride + x0]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_integral_image() {
+        let img =
+            gray_image![
+            1, 2, 3;
+            4, 5, 6;
+            7, 8, 9
+        ];
+        let sums = IntegralImage::new(&img, 0);
+        assert_eq!(sums.sum(Recti::new(0, 0, 3, 3)), 45f64);
+        assert_eq!(sums.sum(Recti::new(1, 1, 2, 2)), 28f64);
+        assert_eq!(sums.sum(Recti::new(2, 0, 1, 0)), 0f64);
+        let squares = IntegralImage::squared(&img, 0);
+        assert_eq!(squares.sum(Recti::new(0, 1, 2, 1)), 41f64);
+    }
+}
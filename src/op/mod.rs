@@ -2,3 +2,11 @@
 pub mod filter;
 pub mod edge_detect;
 pub mod blur;
+pub mod pyramid;
+pub mod integral;
+pub mod template;
+pub mod quantize;
+pub mod composite;
+pub mod arithmetic;
+pub mod stats;
+pub mod quality;
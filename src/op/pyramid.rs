@@ -0,0 +1,246 @@
+use image::{Image, ImageGrayf};
+use pixel::{Pixel, MAX_CHANNEL_COUNT};
+use num::{Zero, ToPrimitive};
+use eye::Eye;
+use sampler::saturate;
+
+// 5-tap binomial kernel, [1 4 6 4 1] / 16
+const KERNEL: [f32; 5] = [0.0625, 0.25, 0.375, 0.25, 0.0625];
+
+fn to_pixel<P: Pixel>(acc: &[f32]) -> P {
+    let mut out = [P::Subpixel::zero(); MAX_CHANNEL_COUNT];
+    for c in 0..P::channels() {
+        out[c] = saturate(acc[c]);
+    }
+    P::from_raw(&out[..P::channels()])
+}
+
+// combines `a` and `b` channel by channel
+fn zip_with<P, F>(a: &Image<P>, b: &Image<P>, f: F) -> Image<P>
+where
+    P: Pixel,
+    F: Fn(usize, usize, f32, f32) -> f32,
+{
+    assert_eq!(a.size(), b.size());
+    let mut dst = Image::new(a.width(), a.height());
+    let mut acc = [0f32; MAX_CHANNEL_COUNT];
+    for (x, y, p) in dst.iter_mut() {
+        let pa = a[(x, y)];
+        let pb = b[(x, y)];
+        for c in 0..P::channels() {
+            acc[c] = f(
+                x,
+                y,
+                pa.raw()[c].to_f32().unwrap(),
+                pb.raw()[c].to_f32().unwrap(),
+            );
+        }
+        *p = to_pixel(&acc);
+    }
+    dst
+}
+
+/// Blurs `src` with the 5x5 Gaussian kernel and drops every other row and
+/// column; the result is `((width + 1) / 2, (height + 1) / 2)`.
+pub fn pyr_down<P: Pixel>(src: &Image<P>) -> Image<P> {
+    let width = (src.width() + 1) / 2;
+    let height = (src.height() + 1) / 2;
+    let mut dst = Image::new(width, height);
+    let eye = Eye::default().mirror();
+    let mut acc = [0f32; MAX_CHANNEL_COUNT];
+    for (x, y, p) in dst.iter_mut() {
+        acc.iter_mut().for_each(|v| *v = 0f32);
+        for j in 0..5 {
+            let sy = 2 * y as isize + j as isize - 2;
+            for i in 0..5 {
+                let sx = 2 * x as isize + i as isize - 2;
+                let w = KERNEL[i] * KERNEL[j];
+                let s = eye.x(sx).y(sy).look(src);
+                for c in 0..P::channels() {
+                    acc[c] += s.raw()[c].to_f32().unwrap() * w;
+                }
+            }
+        }
+        *p = to_pixel(&acc);
+    }
+    dst
+}
+
+/// Upsamples `src` to `width` x `height` by inserting zero rows and columns
+/// and smoothing with four times the 5x5 Gaussian kernel. The size is
+/// normally twice that of `src`, minus one for odd sizes of the finer level.
+pub fn pyr_up<P: Pixel>(src: &Image<P>, width: usize, height: usize) -> Image<P> {
+    let mut dst = Image::new(width, height);
+    let eye = Eye::default().mirror();
+    let mut acc = [0f32; MAX_CHANNEL_COUNT];
+    for (x, y, p) in dst.iter_mut() {
+        acc.iter_mut().for_each(|v| *v = 0f32);
+        for j in 0..5 {
+            let uy = y as isize + j as isize - 2;
+            if uy & 1 != 0 {
+                continue;
+            }
+            for i in 0..5 {
+                let ux = x as isize + i as isize - 2;
+                if ux & 1 != 0 {
+                    continue;
+                }
+                let w = 4f32 * KERNEL[i] * KERNEL[j];
+                let s = eye.x(ux / 2).y(uy / 2).look(src);
+                for c in 0..P::channels() {
+                    acc[c] += s.raw()[c].to_f32().unwrap() * w;
+                }
+            }
+        }
+        *p = to_pixel(&acc);
+    }
+    dst
+}
+
+/// Successively downsampled copies of an image, finest first.
+#[derive(Debug, Clone)]
+pub struct GaussianPyramid<P: Pixel> {
+    pub levels: Vec<Image<P>>,
+}
+
+impl<P: Pixel> GaussianPyramid<P> {
+    /// Builds up to `levels` levels, stopping early once a level is a single
+    /// row or column.
+    pub fn new(src: &Image<P>, levels: usize) -> GaussianPyramid<P> {
+        assert!(levels > 0);
+        let mut pyramid = vec![src.clone()];
+        while pyramid.len() < levels {
+            let next = {
+                let last = &pyramid[pyramid.len() - 1];
+                if last.width() < 2 || last.height() < 2 {
+                    break;
+                }
+                pyr_down(last)
+            };
+            pyramid.push(next);
+        }
+        GaussianPyramid { levels: pyramid }
+    }
+}
+
+/// Band-pass decomposition of an image, finest first; the last level is the
+/// coarsest Gaussian level.
+///
+/// Band-pass levels are signed, so the pixel type should be floating point
+/// (e.g. `ImageGrayf`) for reconstruction to be exact.
+#[derive(Debug, Clone)]
+pub struct LaplacianPyramid<P: Pixel> {
+    pub levels: Vec<Image<P>>,
+}
+
+impl<P: Pixel> LaplacianPyramid<P> {
+    pub fn new(src: &Image<P>, levels: usize) -> LaplacianPyramid<P> {
+        LaplacianPyramid::from_gaussian(&GaussianPyramid::new(src, levels))
+    }
+
+    pub fn from_gaussian(gaussian: &GaussianPyramid<P>) -> LaplacianPyramid<P> {
+        let g = &gaussian.levels;
+        let mut levels = Vec::with_capacity(g.len());
+        for i in 0..g.len() - 1 {
+            let up = pyr_up(&g[i + 1], g[i].width(), g[i].height());
+            levels.push(zip_with(&g[i], &up, |_, _, a, b| a - b));
+        }
+        levels.push(g[g.len() - 1].clone());
+        LaplacianPyramid { levels: levels }
+    }
+
+    /// Collapses the pyramid back into a full resolution image.
+    pub fn reconstruct(&self) -> Image<P> {
+        let mut cur = self.levels[self.levels.len() - 1].clone();
+        for level in self.levels.iter().rev().skip(1) {
+            let up = pyr_up(&cur, level.width(), level.height());
+            cur = zip_with(level, &up, |_, _, a, b| a + b);
+        }
+        cur
+    }
+}
+
+/// Blends `a` and `b` band by band (Burt and Adelson). `mask` holds the
+/// weight of `a` in `[0, 1]` and is smoothed by its own Gaussian pyramid, so
+/// seams are blended over a width that grows with the band.
+pub fn pyramid_blend<P: Pixel>(
+    a: &Image<P>,
+    b: &Image<P>,
+    mask: &ImageGrayf,
+    levels: usize,
+) -> Image<P> {
+    assert_eq!(a.size(), b.size());
+    assert_eq!(a.size(), mask.size());
+    let la = LaplacianPyramid::new(a, levels);
+    let lb = LaplacianPyramid::new(b, levels);
+    let gm = GaussianPyramid::new(mask, levels);
+    let blended = la.levels
+        .iter()
+        .zip(lb.levels.iter())
+        .zip(gm.levels.iter())
+        .map(|((la, lb), m)| {
+            zip_with(la, lb, |x, y, va, vb| {
+                let w = m[(x, y)][0];
+                va * w + vb * (1f32 - w)
+            })
+        })
+        .collect();
+    LaplacianPyramid { levels: blended }.reconstruct()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+    use image::ImageGray;
+    use transform::flip_horizontal;
+    use imageio::{ImageIO, FreeImageIO};
+    use convert::{convert, MapGrayGrayf};
+    use pixel::gray;
+
+    #[test]
+    fn test_pyr_down_up() {
+        let mut flat = ImageGrayf::new(9, 6);
+        flat.fill(&gray(3f32));
+        let down = pyr_down(&flat);
+        assert_eq!(down.size(), (5, 3));
+        assert!(down.iter().all(|(_, _, p)| (p[0] - 3f32).abs() < 1e-5));
+        let up = pyr_up(&down, 9, 6);
+        assert!(up.iter().all(|(_, _, p)| (p[0] - 3f32).abs() < 1e-5));
+
+        let img =
+            gray_image![
+            0u8, 10, 20, 30;
+            40, 50, 60, 70;
+            80, 90, 100, 110
+        ];
+        let pyramid = GaussianPyramid::new(&img, 5);
+        assert_eq!(pyramid.levels.len(), 3);
+        assert_eq!(pyramid.levels[1].size(), (2, 2));
+        assert_eq!(pyramid.levels[2].size(), (1, 1));
+    }
+
+    #[test]
+    fn test_laplacian_pyramid() {
+        let path = Path::new("./tests/cat.jpg");
+        let img: ImageGray = FreeImageIO::from_path(&path).unwrap();
+        let img = convert::<MapGrayGrayf>(&img);
+
+        let pyramid = LaplacianPyramid::new(&img, 4);
+        assert_eq!(pyramid.levels.len(), 4);
+        let out = pyramid.reconstruct();
+        for (x, y, p) in out.iter() {
+            assert!((p[0] - img[(x, y)][0]).abs() < 1e-2);
+        }
+
+        // left half from `img`, right half from its mirror image
+        let flipped = flip_horizontal(&img);
+        let mut mask = ImageGrayf::new(img.width(), img.height());
+        for (x, _, p) in mask.iter_mut() {
+            *p = gray(if x < img.width() / 2 { 1f32 } else { 0f32 });
+        }
+        let out = pyramid_blend(&img, &flipped, &mask, 5);
+        assert_eq!(out.size(), img.size());
+        assert!((out[(0, 0)][0] - img[(0, 0)][0]).abs() < 1f32);
+    }
+}
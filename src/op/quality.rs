@@ -0,0 +1,398 @@
+use image::{Image, ImageGrayf, ImageError};
+use pixel::{Pixel, full_scale, gray};
+use eye::Eye;
+use num::ToPrimitive;
+
+/// Scale weights of MS-SSIM from Wang, Simoncelli and Bovik (2003), finest
+/// scale first.
+pub const MS_SSIM_WEIGHTS: [f32; 5] = [0.0448, 0.2856, 0.3001, 0.2363, 0.1333];
+
+/// A quality score and, if requested, its per-pixel map averaged over the
+/// channels.
+#[derive(Debug, Clone)]
+pub struct Score {
+    pub value: f64,
+    pub map: Option<ImageGrayf>,
+}
+
+fn check_size<P: Pixel>(a: &Image<P>, b: &Image<P>) -> Result<(), ImageError> {
+    if a.size() == b.size() {
+        Ok(())
+    } else {
+        Err(ImageError::SizeMismatch)
+    }
+}
+
+// channel `c` of `img` as floats
+fn plane<P: Pixel>(img: &Image<P>, c: usize) -> ImageGrayf {
+    img.map(|p| gray(p.raw()[c].to_f32().unwrap()))
+}
+
+fn mean(map: &ImageGrayf) -> f64 {
+    let sum: f64 = map.raw().iter().map(|&v| v as f64).sum();
+    sum / map.raw().len() as f64
+}
+
+// squared error averaged over the channels
+fn squared_error<P: Pixel>(a: &Image<P>, b: &Image<P>) -> ImageGrayf {
+    a.zip_map(b, |pa, pb| {
+        let mut acc = 0f32;
+        for c in 0..P::channels() {
+            let d = pa.raw()[c].to_f32().unwrap() - pb.raw()[c].to_f32().unwrap();
+            acc += d * d;
+        }
+        gray(acc / P::channels() as f32)
+    })
+}
+
+/// Mean squared error over all channels. The map holds the squared error
+/// of every pixel.
+pub fn mse<P: Pixel>(a: &Image<P>, b: &Image<P>, map: bool) -> Result<Score, ImageError> {
+    check_size(a, b)?;
+    let err = squared_error(a, b);
+    Ok(Score {
+        value: mean(&err),
+        map: if map { Some(err) } else { None },
+    })
+}
+
+/// Peak signal to noise ratio in dB, with the peak being the maximum of an
+/// integer subpixel or 1 for floats. Identical images score infinity, as do
+/// identical pixels in the map.
+pub fn psnr<P: Pixel>(a: &Image<P>, b: &Image<P>, map: bool) -> Result<Score, ImageError> {
+    check_size(a, b)?;
+    let peak = full_scale::<P::Subpixel>() as f64;
+    let err = squared_error(a, b);
+    let value = 10f64 * (peak * peak / mean(&err)).log10();
+    let map = if map {
+        Some(err.map(|e| gray((10f64 * (peak * peak / e[0] as f64).log10()) as f32)))
+    } else {
+        None
+    };
+    Ok(Score { value: value, map: map })
+}
+
+// separable smoothing with `kernel`, mirroring at the borders
+fn smooth(src: &ImageGrayf, kernel: &[f32]) -> ImageGrayf {
+    let r = (kernel.len() / 2) as isize;
+    let eye = Eye::default().mirror();
+    let mut tmp = ImageGrayf::new(src.width(), src.height());
+    for (x, y, p) in tmp.iter_mut() {
+        let mut acc = 0f32;
+        for (i, w) in kernel.iter().enumerate() {
+            acc += w * eye.x(x as isize + i as isize - r).y(y as isize).look(src)[0];
+        }
+        *p = gray(acc);
+    }
+    let mut dst = ImageGrayf::new(src.width(), src.height());
+    for (x, y, p) in dst.iter_mut() {
+        let mut acc = 0f32;
+        for (i, w) in kernel.iter().enumerate() {
+            acc += w * eye.x(x as isize).y(y as isize + i as isize - r).look(&tmp)[0];
+        }
+        *p = gray(acc);
+    }
+    dst
+}
+
+// averages 2x2 blocks, repeating the last row and column of odd sizes
+fn half(src: &ImageGrayf) -> ImageGrayf {
+    let (w, h) = src.size();
+    let mut dst = ImageGrayf::new((w + 1) / 2, (h + 1) / 2);
+    for (x, y, p) in dst.iter_mut() {
+        let (x0, y0) = (2 * x, 2 * y);
+        let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+        let sum = src[(x0, y0)][0] + src[(x1, y0)][0] + src[(x0, y1)][0] + src[(x1, y1)][0];
+        *p = gray(sum / 4f32);
+    }
+    dst
+}
+
+// mean of `map` over the pixels whose window lies inside the image
+fn interior_mean(map: &ImageGrayf, r: usize) -> f64 {
+    let mut sum = 0f64;
+    for y in r..map.height() - r {
+        for x in r..map.width() - r {
+            sum += map[(x, y)][0] as f64;
+        }
+    }
+    sum / ((map.width() - 2 * r) * (map.height() - 2 * r)) as f64
+}
+
+/// Structural similarity (Wang et al., 2004) with a Gaussian window, and
+/// its multi-scale variant MS-SSIM.
+///
+/// Local statistics are weighted by the window; the score averages the map
+/// over the pixels whose window lies inside the image, like the reference
+/// implementation, and over the channels.
+#[derive(Debug, Clone)]
+pub struct Ssim {
+    window: usize,
+    sigma: f32,
+    k1: f32,
+    k2: f32,
+    range: Option<f32>,
+    weights: Vec<f32>,
+    map: bool,
+}
+
+impl Ssim {
+    /// An 11x11 window with sigma 1.5, `K1 = 0.01` and `K2 = 0.03`.
+    pub fn new() -> Self {
+        Ssim {
+            window: 11,
+            sigma: 1.5,
+            k1: 0.01,
+            k2: 0.03,
+            range: None,
+            weights: MS_SSIM_WEIGHTS.to_vec(),
+            map: false,
+        }
+    }
+
+    /// Window size, which must be odd, and the sigma of its Gaussian.
+    pub fn window(mut self, size: usize, sigma: f32) -> Self {
+        assert_eq!(size & 1, 1);
+        self.window = size;
+        self.sigma = sigma;
+        self
+    }
+
+    /// Stabilizing constants `K1` and `K2`.
+    pub fn constants(mut self, k1: f32, k2: f32) -> Self {
+        self.k1 = k1;
+        self.k2 = k2;
+        self
+    }
+
+    /// Dynamic range of the values; by default the maximum of an integer
+    /// subpixel or 1 for floats.
+    pub fn data_range(mut self, range: f32) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// MS-SSIM weights, one per scale and finest first.
+    pub fn weights(mut self, weights: &[f32]) -> Self {
+        assert!(!weights.is_empty());
+        self.weights = weights.to_vec();
+        self
+    }
+
+    /// Whether to return the per-pixel map along with the score.
+    pub fn with_map(mut self, map: bool) -> Self {
+        self.map = map;
+        self
+    }
+
+    fn kernel(&self) -> Vec<f32> {
+        let r = (self.window / 2) as f32;
+        let mut kernel: Vec<f32> = (0..self.window)
+            .map(|i| {
+                let d = i as f32 - r;
+                (-d * d / (2f32 * self.sigma * self.sigma)).exp()
+            })
+            .collect();
+        let sum: f32 = kernel.iter().sum();
+        kernel.iter_mut().for_each(|w| *w /= sum);
+        kernel
+    }
+
+    // SSIM and contrast-structure maps of two planes
+    fn maps<P: Pixel>(&self, a: &ImageGrayf, b: &ImageGrayf) -> (ImageGrayf, ImageGrayf) {
+        let kernel = self.kernel();
+        let range = self.range.unwrap_or(full_scale::<P::Subpixel>());
+        let c1 = (self.k1 * range) * (self.k1 * range);
+        let c2 = (self.k2 * range) * (self.k2 * range);
+        // the statistics are shift invariant; centering the planes keeps
+        // the variances from cancelling out in f32
+        let (ka, kb) = (mean(a) as f32, mean(b) as f32);
+        let a = a.map(|p| gray(p[0] - ka));
+        let b = b.map(|p| gray(p[0] - kb));
+        let mu_a = smooth(&a, &kernel);
+        let mu_b = smooth(&b, &kernel);
+        let aa = smooth(&a.map(|p| gray(p[0] * p[0])), &kernel);
+        let bb = smooth(&b.map(|p| gray(p[0] * p[0])), &kernel);
+        let ab = smooth(&a.zip_map(&b, |p, q| gray(p[0] * q[0])), &kernel);
+        let mut ssim = ImageGrayf::new(a.width(), a.height());
+        let mut cs = ImageGrayf::new(a.width(), a.height());
+        for (x, y, p) in ssim.iter_mut() {
+            let (ma, mb) = (mu_a[(x, y)][0], mu_b[(x, y)][0]);
+            let va = aa[(x, y)][0] - ma * ma;
+            let vb = bb[(x, y)][0] - mb * mb;
+            let cov = ab[(x, y)][0] - ma * mb;
+            let (ma, mb) = (ma + ka, mb + kb);
+            let l = (2f32 * ma * mb + c1) / (ma * ma + mb * mb + c1);
+            let s = (2f32 * cov + c2) / (va + vb + c2);
+            cs[(x, y)] = gray(s);
+            *p = gray(l * s);
+        }
+        (ssim, cs)
+    }
+
+    /// SSIM of `a` and `b`, which must be at least the window size.
+    pub fn ssim<P: Pixel>(&self, a: &Image<P>, b: &Image<P>) -> Result<Score, ImageError> {
+        check_size(a, b)?;
+        if a.width() < self.window || a.height() < self.window {
+            return Err(ImageError::InvalidImage);
+        }
+        let n = P::channels();
+        let mut value = 0f64;
+        let mut map = ImageGrayf::new(a.width(), a.height());
+        map.zero();
+        for c in 0..n {
+            let (ssim, _) = self.maps::<P>(&plane(a, c), &plane(b, c));
+            value += interior_mean(&ssim, self.window / 2);
+            map = map.zip_map(&ssim, |m, s| gray(m[0] + s[0] / n as f32));
+        }
+        Ok(Score {
+            value: value / n as f64,
+            map: if self.map { Some(map) } else { None },
+        })
+    }
+
+    /// MS-SSIM of `a` and `b`: the contrast-structure terms of the finer
+    /// scales and the SSIM of the coarsest one, raised to their weights and
+    /// multiplied. Every scale halves the size by averaging 2x2 blocks, and
+    /// the coarsest must still be at least the window size. Negative terms
+    /// count as 0. The map combines the maps of all scales at full size.
+    pub fn ms_ssim<P: Pixel>(&self, a: &Image<P>, b: &Image<P>) -> Result<Score, ImageError> {
+        check_size(a, b)?;
+        let (mut w, mut h) = a.size();
+        for _ in 1..self.weights.len() {
+            w = (w + 1) / 2;
+            h = (h + 1) / 2;
+        }
+        if w < self.window || h < self.window {
+            return Err(ImageError::InvalidImage);
+        }
+        let n = P::channels();
+        let last = self.weights.len() - 1;
+        let mut value = 0f64;
+        let mut map = ImageGrayf::new(a.width(), a.height());
+        map.zero();
+        for c in 0..n {
+            let (mut pa, mut pb) = (plane(a, c), plane(b, c));
+            let mut score = 1f64;
+            let mut channel_map = ImageGrayf::new(a.width(), a.height());
+            channel_map.fill(&gray(1f32));
+            for (j, &weight) in self.weights.iter().enumerate() {
+                if j > 0 {
+                    pa = half(&pa);
+                    pb = half(&pb);
+                }
+                let (ssim, cs) = self.maps::<P>(&pa, &pb);
+                let term = if j == last { ssim } else { cs };
+                let m = interior_mean(&term, self.window / 2);
+                score *= m.max(0f64).powf(weight as f64);
+                for (x, y, p) in channel_map.iter_mut() {
+                    let t = term[(x >> j, y >> j)][0].max(0f32).powf(weight);
+                    *p = gray(p[0] * t);
+                }
+            }
+            value += score;
+            map = map.zip_map(&channel_map, |m, s| gray(m[0] + s[0] / n as f32));
+        }
+        Ok(Score {
+            value: value / n as f64,
+            map: if self.map { Some(map) } else { None },
+        })
+    }
+}
+
+impl Default for Ssim {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{ImageGray, ImageBGR};
+    use pixel::{Gray, bgr};
+
+    // deterministic test pattern and a distorted copy of it
+    fn pattern(width: usize, height: usize) -> (ImageGray, ImageGray) {
+        let mut a = ImageGray::new(width, height);
+        let mut b = ImageGray::new(width, height);
+        for (x, y, p) in a.iter_mut() {
+            *p = gray(((x * x * 3 + y * 7 + x * y) % 256) as u8);
+        }
+        for (x, y, p) in b.iter_mut() {
+            let v = a[(x, y)][0] as isize + ((x * 13 + y * 29) % 41) as isize - 20;
+            *p = gray(v.max(0).min(255) as u8);
+        }
+        (a, b)
+    }
+
+    fn constant(size: usize, v: u8) -> ImageGray {
+        let mut img = ImageGray::new(size, size);
+        img.fill(&gray(v));
+        img
+    }
+
+    #[test]
+    fn test_mse_psnr() {
+        let a = gray_image![10, 20, 30; 40, 50, 60];
+        let b = gray_image![20, 30, 40; 50, 60, 70];
+        let s = mse(&a, &b, true).unwrap();
+        assert_eq!(s.value, 100f64);
+        assert_eq!(s.map.unwrap()[(2, 1)], Gray([100f32]));
+        let p = psnr(&a, &b, false).unwrap();
+        assert!((p.value - 28.130803608679102).abs() < 1e-9);
+        assert!(p.map.is_none());
+        assert!(psnr(&a, &a, false).unwrap().value.is_infinite());
+
+        let mut c = ImageBGR::new(1, 1);
+        let mut d = ImageBGR::new(1, 1);
+        c[(0, 0)] = bgr(0, 0, 0);
+        d[(0, 0)] = bgr(3, 0, 0);
+        assert_eq!(mse(&c, &d, false).unwrap().value, 3f64);
+        match mse(&a, &gray_image![1, 2], false) {
+            Err(ImageError::SizeMismatch) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ssim() {
+        let (a, b) = pattern(32, 24);
+        let ssim = Ssim::new().with_map(true);
+        let same = ssim.ssim(&a, &a).unwrap();
+        assert!((same.value - 1f64).abs() < 1e-6);
+        assert!(same.map.unwrap().raw().iter().all(|&v| (v - 1f32).abs() < 1e-4));
+
+        // constant images only differ in luminance
+        let (c1, l) = (6.5025f64, 2f64 * 100f64 * 120f64);
+        let expect = (l + c1) / (100f64 * 100f64 + 120f64 * 120f64 + c1);
+        let s = ssim.ssim(&constant(16, 100), &constant(16, 120)).unwrap();
+        assert!((s.value - expect).abs() < 1e-6, "{} {}", s.value, expect);
+
+        // float64 reference implementation of Wang et al.
+        let s = ssim.ssim(&a, &b).unwrap();
+        assert!((s.value - 0.9878520).abs() < 1e-4, "{}", s.value);
+        match ssim.ssim(&constant(8, 0), &constant(8, 0)) {
+            Err(ImageError::InvalidImage) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ms_ssim() {
+        let ssim = Ssim::new().with_map(true);
+        let (a, b) = pattern(176, 176);
+        let same = ssim.ms_ssim(&a, &a).unwrap();
+        assert!((same.value - 1f64).abs() < 1e-5);
+        assert_eq!(same.map.unwrap().size(), (176, 176));
+
+        let (c1, l) = (6.5025f64, 2f64 * 100f64 * 120f64);
+        let l = (l + c1) / (100f64 * 100f64 + 120f64 * 120f64 + c1);
+        let s = ssim.ms_ssim(&constant(176, 100), &constant(176, 120)).unwrap();
+        assert!((s.value - l.powf(0.1333)).abs() < 1e-5, "{} {}", s.value, l.powf(0.1333));
+
+        let s = ssim.ms_ssim(&a, &b).unwrap();
+        assert!((s.value - 0.9919419).abs() < 1e-4, "{}", s.value);
+        assert!(ssim.ms_ssim(&constant(100, 0), &constant(100, 0)).is_err());
+    }
+}
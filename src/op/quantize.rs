@@ -0,0 +1,401 @@
+use std::f32;
+use image::{Image, ImageGray};
+use pixel::{Pixel, Gray, MAX_CHANNEL_COUNT};
+use math::utils::XorShiftRng;
+use sampler::saturate;
+use num::ToPrimitive;
+
+/// How colors are mapped to their nearest palette entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dither {
+    None,
+    /// error diffusion (Floyd and Steinberg, 1976)
+    FloydSteinberg,
+    /// 4x4 Bayer threshold matrix
+    Ordered,
+}
+
+type Color = [f32; MAX_CHANNEL_COUNT];
+
+fn to_color<P: Pixel>(p: &P) -> Color {
+    let mut c = [0f32; MAX_CHANNEL_COUNT];
+    for (v, s) in c.iter_mut().zip(p.raw()) {
+        *v = s.to_f32().unwrap();
+    }
+    c
+}
+
+fn to_pixel<P: Pixel>(c: &Color) -> P {
+    let mut p = P::zero();
+    for (s, &v) in p.raw_mut().iter_mut().zip(c.iter()) {
+        *s = saturate(v);
+    }
+    p
+}
+
+fn distance2(a: &Color, b: &Color) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn nearest(c: &Color, palette: &[Color]) -> usize {
+    let mut best = (0, f32::INFINITY);
+    for (i, p) in palette.iter().enumerate() {
+        let d = distance2(c, p);
+        if d < best.1 {
+            best = (i, d);
+        }
+    }
+    best.0
+}
+
+/// Color quantization by k-means clustering with k-means++ seeding
+/// (Arthur and Vassilvitskii, 2007).
+#[derive(Debug, Clone)]
+pub struct KMeans {
+    k: usize,
+    iterations: usize,
+    seed: u64,
+    dither: Dither,
+}
+
+impl Default for KMeans {
+    fn default() -> KMeans {
+        KMeans::new(16)
+    }
+}
+
+impl KMeans {
+    /// At most `k` colors, `k <= 256`.
+    pub fn new(k: usize) -> KMeans {
+        assert!(k > 0 && k <= 256);
+        KMeans {
+            k: k,
+            iterations: 20,
+            seed: 0,
+            dither: Dither::None,
+        }
+    }
+
+    pub fn iterations(mut self, n: usize) -> Self {
+        self.iterations = n;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// The cluster centers of the colors of `img`.
+    pub fn palette<P: Pixel>(&self, img: &Image<P>) -> Vec<P> {
+        let colors: Vec<Color> = img.iter().map(|(_, _, p)| to_color(p)).collect();
+        if colors.is_empty() {
+            return Vec::new();
+        }
+        let mut rng = XorShiftRng::new(self.seed);
+        let mut centers = vec![colors[rng.gen_range(colors.len())]];
+        let mut d2: Vec<f32> = colors.iter().map(|c| distance2(c, &centers[0])).collect();
+        while centers.len() < self.k {
+            // next center drawn with probability proportional to the squared
+            // distance to the closest center so far
+            let total: f32 = d2.iter().sum();
+            if total <= 0f32 {
+                break;
+            }
+            let mut target = rng.next_f32() * total;
+            let mut pick = colors.len() - 1;
+            for (i, &d) in d2.iter().enumerate() {
+                if target < d {
+                    pick = i;
+                    break;
+                }
+                target -= d;
+            }
+            let c = colors[pick];
+            for (d, x) in d2.iter_mut().zip(colors.iter()) {
+                *d = d.min(distance2(x, &c));
+            }
+            centers.push(c);
+        }
+
+        let k = centers.len();
+        let mut assign = vec![0; colors.len()];
+        for _ in 0..self.iterations {
+            let mut changed = false;
+            for (a, c) in assign.iter_mut().zip(colors.iter()) {
+                let i = nearest(c, &centers);
+                changed |= *a != i;
+                *a = i;
+            }
+            let mut sums = vec![[0f32; MAX_CHANNEL_COUNT]; k];
+            let mut counts = vec![0usize; k];
+            for (&a, c) in assign.iter().zip(colors.iter()) {
+                for (s, v) in sums[a].iter_mut().zip(c.iter()) {
+                    *s += *v;
+                }
+                counts[a] += 1;
+            }
+            for ((center, s), &n) in centers.iter_mut().zip(sums.iter()).zip(counts.iter()) {
+                // empty clusters keep their center
+                if n > 0 {
+                    for (v, s) in center.iter_mut().zip(s.iter()) {
+                        *v = s / n as f32;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        centers.iter().map(to_pixel).collect()
+    }
+
+    /// Returns the palette and the index of every pixel into it.
+    pub fn quantize<P: Pixel>(&self, img: &Image<P>) -> (Vec<P>, ImageGray) {
+        let palette = self.palette(img);
+        let indices = map_to_palette(img, &palette, self.dither);
+        (palette, indices)
+    }
+}
+
+/// Color quantization by median cut (Heckbert, 1982).
+#[derive(Debug, Clone)]
+pub struct MedianCut {
+    k: usize,
+    dither: Dither,
+}
+
+impl Default for MedianCut {
+    fn default() -> MedianCut {
+        MedianCut::new(16)
+    }
+}
+
+impl MedianCut {
+    /// At most `k` colors, `k <= 256`.
+    pub fn new(k: usize) -> MedianCut {
+        assert!(k > 0 && k <= 256);
+        MedianCut {
+            k: k,
+            dither: Dither::None,
+        }
+    }
+
+    pub fn dither(mut self, dither: Dither) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Repeatedly splits the color box with the widest channel range at the
+    /// median of that channel, and returns the mean color of every box.
+    pub fn palette<P: Pixel>(&self, img: &Image<P>) -> Vec<P> {
+        let ch = P::channels();
+        let colors: Vec<Color> = img.iter().map(|(_, _, p)| to_color(p)).collect();
+        if colors.is_empty() {
+            return Vec::new();
+        }
+        // widest channel and its range
+        let widest = |b: &[Color]| {
+            let mut best = (0, 0f32);
+            for c in 0..ch {
+                let lo = b.iter().map(|v| v[c]).fold(f32::INFINITY, f32::min);
+                let hi = b.iter().map(|v| v[c]).fold(f32::NEG_INFINITY, f32::max);
+                if hi - lo > best.1 {
+                    best = (c, hi - lo);
+                }
+            }
+            best
+        };
+        let mut boxes = vec![colors];
+        while boxes.len() < self.k {
+            let (i, c, range) = boxes
+                .iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    let (c, range) = widest(b);
+                    (i, c, range)
+                })
+                .fold((0, 0, 0f32), |best, x| if x.2 > best.2 { x } else { best });
+            if range <= 0f32 {
+                break;
+            }
+            let mut b = boxes.swap_remove(i);
+            b.sort_by(|x, y| x[c].partial_cmp(&y[c]).unwrap());
+            // split between distinct values, so neither half is empty
+            let mut m = b.len() / 2;
+            while m > 0 && b[m - 1][c] == b[m][c] {
+                m -= 1;
+            }
+            if m == 0 {
+                m = b.len() / 2;
+                while b[m - 1][c] == b[m][c] {
+                    m += 1;
+                }
+            }
+            let upper = b.split_off(m);
+            boxes.push(b);
+            boxes.push(upper);
+        }
+        boxes
+            .iter()
+            .map(|b| {
+                let mut mean = [0f32; MAX_CHANNEL_COUNT];
+                for v in b.iter() {
+                    for (m, x) in mean.iter_mut().zip(v.iter()) {
+                        *m += *x;
+                    }
+                }
+                for m in mean.iter_mut() {
+                    *m /= b.len() as f32;
+                }
+                to_pixel(&mean)
+            })
+            .collect()
+    }
+
+    /// Returns the palette and the index of every pixel into it.
+    pub fn quantize<P: Pixel>(&self, img: &Image<P>) -> (Vec<P>, ImageGray) {
+        let palette = self.palette(img);
+        let indices = map_to_palette(img, &palette, self.dither);
+        (palette, indices)
+    }
+}
+
+const BAYER: [[f32; 4]; 4] = [
+    [0f32, 8f32, 2f32, 10f32],
+    [12f32, 4f32, 14f32, 6f32],
+    [3f32, 11f32, 1f32, 9f32],
+    [15f32, 7f32, 13f32, 5f32],
+];
+
+/// Index of the palette entry every pixel of `img` maps to.
+pub fn map_to_palette<P: Pixel>(img: &Image<P>, palette: &[P], dither: Dither) -> ImageGray {
+    assert!(!palette.is_empty() && palette.len() <= 256);
+    let (w, h) = img.size();
+    let colors: Vec<Color> = palette.iter().map(to_color).collect();
+    let ch = P::channels();
+    let mut dst = ImageGray::new(w, h);
+    match dither {
+        Dither::None => {
+            for (x, y, p) in dst.iter_mut() {
+                *p = Gray([nearest(&to_color(&img[(x, y)]), &colors) as u8]);
+            }
+        }
+        Dither::FloydSteinberg => {
+            // error carried to the current and the next row
+            let mut err = vec![[0f32; MAX_CHANNEL_COUNT]; 2 * (w + 2)];
+            for y in 0..h {
+                let (cur, next) = err.split_at_mut(w + 2);
+                for e in next.iter_mut() {
+                    *e = [0f32; MAX_CHANNEL_COUNT];
+                }
+                for x in 0..w {
+                    let mut c = to_color(&img[(x, y)]);
+                    for i in 0..ch {
+                        c[i] += cur[x + 1][i];
+                    }
+                    let k = nearest(&c, &colors);
+                    dst[(x, y)] = Gray([k as u8]);
+                    for i in 0..ch {
+                        let e = c[i] - colors[k][i];
+                        cur[x + 2][i] += e * 7f32 / 16f32;
+                        next[x][i] += e * 3f32 / 16f32;
+                        next[x + 1][i] += e * 5f32 / 16f32;
+                        next[x + 2][i] += e / 16f32;
+                    }
+                }
+                let (a, b) = err.split_at_mut(w + 2);
+                a.copy_from_slice(b);
+            }
+        }
+        Dither::Ordered => {
+            // threshold amplitude of the typical gap between palette colors
+            let spread = if colors.len() < 2 {
+                0f32
+            } else {
+                colors
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| {
+                        colors
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| j != i)
+                            .map(|(_, b)| distance2(a, b))
+                            .fold(f32::INFINITY, f32::min)
+                            .sqrt()
+                    })
+                    .sum::<f32>() / colors.len() as f32
+            };
+            for (x, y, p) in dst.iter_mut() {
+                let t = (BAYER[y % 4][x % 4] + 0.5) / 16f32 - 0.5;
+                let mut c = to_color(&img[(x, y)]);
+                for i in 0..ch {
+                    c[i] += t * spread;
+                }
+                *p = Gray([nearest(&c, &colors) as u8]);
+            }
+        }
+    }
+    dst
+}
+
+/// Image with the palette color of every index.
+pub fn from_palette<P: Pixel>(indices: &ImageGray, palette: &[P]) -> Image<P> {
+    let (w, h) = indices.size();
+    let mut dst = Image::new(w, h);
+    for (x, y, p) in dst.iter_mut() {
+        *p = palette[indices[(x, y)][0] as usize];
+    }
+    dst
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageBGR;
+    use pixel::{bgr, gray};
+
+    #[test]
+    fn test_quantize() {
+        let colors = [bgr(10, 20, 200), bgr(200, 30, 10), bgr(50, 180, 60), bgr(0, 0, 0)];
+        let mut img = ImageBGR::new(16, 16);
+        for (x, y, p) in img.iter_mut() {
+            let c = colors[(x / 8) + 2 * (y / 8)];
+            // slight noise around each color
+            *p = bgr(c[0] + (x % 3) as u8, c[1], c[2] + (y % 2) as u8);
+        }
+        for palette in [KMeans::new(4).palette(&img), MedianCut::new(4).palette(&img)].iter() {
+            assert_eq!(palette.len(), 4);
+            for c in colors.iter() {
+                assert!(palette.iter().any(|p| {
+                    (0..3).all(|i| (p[i] as i32 - c[i] as i32).abs() <= 2)
+                }));
+            }
+        }
+        let (palette, indices) = MedianCut::new(4).quantize(&img);
+        let out = from_palette(&indices, &palette);
+        assert_eq!(out[(0, 0)], out[(7, 7)]);
+        assert!(out[(0, 0)] != out[(8, 0)]);
+    }
+
+    #[test]
+    fn test_dither() {
+        // a flat gray of 64 is a quarter white with black and white only
+        let mut img = ImageGray::new(16, 16);
+        img.fill(&gray(64));
+        let palette = [gray(0), gray(255)];
+        for &d in [Dither::FloydSteinberg, Dither::Ordered].iter() {
+            let indices = map_to_palette(&img, &palette, d);
+            let white = indices.iter().filter(|&(_, _, p)| p[0] == 1).count();
+            assert!((white as i32 - 64).abs() <= 4, "{:?} {}", d, white);
+        }
+        let indices = map_to_palette(&img, &palette, Dither::None);
+        assert!(indices.iter().all(|(_, _, p)| p[0] == 0));
+    }
+}
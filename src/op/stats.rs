@@ -0,0 +1,370 @@
+use image::{Image, ImageBinary, ImageError};
+use pixel::{Pixel, Gray};
+use traits::Primitive;
+use geo::{Point, Pointi};
+use num::ToPrimitive;
+
+/// Extremes of one channel and where they first occur in raster order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinMaxLoc<T> {
+    pub min: T,
+    pub min_loc: Pointi,
+    pub max: T,
+    pub max_loc: Pointi,
+}
+
+// whether (x, y) is selected by `mask`, where non-zero pixels are
+#[inline]
+fn selected(mask: Option<&ImageBinary>, x: usize, y: usize) -> bool {
+    mask.map_or(true, |m| m[(x, y)][0] != 0)
+}
+
+fn check_mask<P: Pixel>(src: &Image<P>, mask: Option<&ImageBinary>) {
+    if let Some(m) = mask {
+        assert_eq!(m.size(), src.size());
+    }
+}
+
+#[inline]
+fn channel<P: Pixel>(p: &P, c: usize) -> f64 {
+    p.raw()[c].to_f64().unwrap()
+}
+
+/// Per channel sum of the pixels selected by `mask`, or of all pixels.
+pub fn sum<P: Pixel>(src: &Image<P>, mask: Option<&ImageBinary>) -> Vec<f64> {
+    check_mask(src, mask);
+    let mut s = vec![0f64; P::channels()];
+    for (_, _, p) in src.iter().filter(|&(x, y, _)| selected(mask, x, y)) {
+        for (c, v) in s.iter_mut().enumerate() {
+            *v += channel(p, c);
+        }
+    }
+    s
+}
+
+/// Per channel mean, 0 if `mask` selects no pixel.
+pub fn mean<P: Pixel>(src: &Image<P>, mask: Option<&ImageBinary>) -> Vec<f64> {
+    mean_std_dev(src, mask).0
+}
+
+/// Per channel mean and population standard deviation.
+pub fn mean_std_dev<P: Pixel>(src: &Image<P>, mask: Option<&ImageBinary>) -> (Vec<f64>, Vec<f64>) {
+    check_mask(src, mask);
+    let ch = P::channels();
+    let (mut s, mut sq) = (vec![0f64; ch], vec![0f64; ch]);
+    let mut n = 0usize;
+    for (_, _, p) in src.iter().filter(|&(x, y, _)| selected(mask, x, y)) {
+        for c in 0..ch {
+            let v = channel(p, c);
+            s[c] += v;
+            sq[c] += v * v;
+        }
+        n += 1;
+    }
+    if n == 0 {
+        return (s, sq.iter().map(|_| 0f64).collect());
+    }
+    let n = n as f64;
+    let mean: Vec<f64> = s.iter().map(|v| v / n).collect();
+    let std_dev = sq.iter()
+        .zip(mean.iter())
+        .map(|(q, m)| (q / n - m * m).max(0f64).sqrt())
+        .collect();
+    (mean, std_dev)
+}
+
+/// Per channel minimum and maximum of the selected pixels with their first
+/// locations in raster order, or `None` if no pixel is selected.
+pub fn min_max_loc<P: Pixel>(
+    src: &Image<P>,
+    mask: Option<&ImageBinary>,
+) -> Option<Vec<MinMaxLoc<P::Subpixel>>> {
+    check_mask(src, mask);
+    let mut locs: Option<Vec<MinMaxLoc<P::Subpixel>>> = None;
+    for (x, y, p) in src.iter().filter(|&(x, y, _)| selected(mask, x, y)) {
+        let at = Point::new(x as isize, y as isize);
+        match locs {
+            None => {
+                locs = Some(
+                    p.raw()
+                        .iter()
+                        .map(|&v| {
+                            MinMaxLoc {
+                                min: v,
+                                min_loc: at,
+                                max: v,
+                                max_loc: at,
+                            }
+                        })
+                        .collect(),
+                )
+            }
+            Some(ref mut locs) => {
+                for (loc, &v) in locs.iter_mut().zip(p.raw()) {
+                    if v < loc.min {
+                        loc.min = v;
+                        loc.min_loc = at;
+                    }
+                    if v > loc.max {
+                        loc.max = v;
+                        loc.max_loc = at;
+                    }
+                }
+            }
+        }
+    }
+    locs
+}
+
+/// Per channel number of non-zero selected pixels.
+pub fn count_non_zero<P: Pixel>(src: &Image<P>, mask: Option<&ImageBinary>) -> Vec<usize> {
+    check_mask(src, mask);
+    let mut n = vec![0usize; P::channels()];
+    for (_, _, p) in src.iter().filter(|&(x, y, _)| selected(mask, x, y)) {
+        for (c, v) in n.iter_mut().enumerate() {
+            if channel(p, c) != 0f64 {
+                *v += 1;
+            }
+        }
+    }
+    n
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormType {
+    /// sum of absolute values
+    L1,
+    /// square root of the sum of squares
+    L2,
+    /// largest absolute value
+    Inf,
+}
+
+fn norm_of<I: Iterator<Item = f64>>(values: I, kind: NormType) -> f64 {
+    let mut acc = 0f64;
+    for v in values {
+        match kind {
+            NormType::L1 => acc += v.abs(),
+            NormType::L2 => acc += v * v,
+            NormType::Inf => acc = acc.max(v.abs()),
+        }
+    }
+    if kind == NormType::L2 { acc.sqrt() } else { acc }
+}
+
+/// Norm of the selected pixels of `src` over all channels.
+pub fn norm<P: Pixel>(src: &Image<P>, kind: NormType, mask: Option<&ImageBinary>) -> f64 {
+    check_mask(src, mask);
+    let values = src.iter()
+        .filter(|&(x, y, _)| selected(mask, x, y))
+        .flat_map(|(_, _, p)| p.raw().iter().map(|v| v.to_f64().unwrap()));
+    norm_of(values, kind)
+}
+
+/// Norm of `a - b`.
+pub fn norm_diff<P: Pixel>(
+    a: &Image<P>,
+    b: &Image<P>,
+    kind: NormType,
+    mask: Option<&ImageBinary>,
+) -> Result<f64, ImageError> {
+    if a.size() != b.size() || mask.map_or(false, |m| m.size() != a.size()) {
+        return Err(ImageError::SizeMismatch);
+    }
+    let values = a.iter()
+        .zip(b.iter())
+        .filter(|&((x, y, _), _)| selected(mask, x, y))
+        .flat_map(|((_, _, p), (_, _, q))| {
+            p.raw().iter().zip(q.raw()).map(|(u, v)| {
+                u.to_f64().unwrap() - v.to_f64().unwrap()
+            })
+        });
+    Ok(norm_of(values, kind))
+}
+
+/// Norm of `a - b` relative to the norm of `b`, infinite if `b` is zero and
+/// `a` is not.
+pub fn norm_relative<P: Pixel>(
+    a: &Image<P>,
+    b: &Image<P>,
+    kind: NormType,
+    mask: Option<&ImageBinary>,
+) -> Result<f64, ImageError> {
+    let diff = norm_diff(a, b, kind, mask)?;
+    let base = norm(b, kind, mask);
+    Ok(if diff == 0f64 { 0f64 } else { diff / base })
+}
+
+/// Image moments up to the third order.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Moments {
+    // spatial
+    pub m00: f64,
+    pub m10: f64,
+    pub m01: f64,
+    pub m20: f64,
+    pub m11: f64,
+    pub m02: f64,
+    pub m30: f64,
+    pub m21: f64,
+    pub m12: f64,
+    pub m03: f64,
+    // central
+    pub mu20: f64,
+    pub mu11: f64,
+    pub mu02: f64,
+    pub mu30: f64,
+    pub mu21: f64,
+    pub mu12: f64,
+    pub mu03: f64,
+    // scale invariant, `mu / m00^(1 + (p + q) / 2)`
+    pub nu20: f64,
+    pub nu11: f64,
+    pub nu02: f64,
+    pub nu30: f64,
+    pub nu21: f64,
+    pub nu12: f64,
+    pub nu03: f64,
+}
+
+impl Moments {
+    /// Center of mass, `None` for zero mass.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.m00 == 0f64 {
+            None
+        } else {
+            Some((self.m10 / self.m00, self.m01 / self.m00))
+        }
+    }
+
+    /// The seven moments of Hu (1962), invariant to translation, scale and
+    /// rotation.
+    pub fn hu(&self) -> [f64; 7] {
+        let (n20, n11, n02) = (self.nu20, self.nu11, self.nu02);
+        let (n30, n21, n12, n03) = (self.nu30, self.nu21, self.nu12, self.nu03);
+        let (a, b) = (n30 + n12, n21 + n03);
+        let (c, d) = (n30 - 3f64 * n12, 3f64 * n21 - n03);
+        [
+            n20 + n02,
+            (n20 - n02).powi(2) + 4f64 * n11 * n11,
+            c * c + d * d,
+            a * a + b * b,
+            c * a * (a * a - 3f64 * b * b) + d * b * (3f64 * a * a - b * b),
+            (n20 - n02) * (a * a - b * b) + 4f64 * n11 * a * b,
+            d * a * (a * a - 3f64 * b * b) - c * b * (3f64 * a * a - b * b),
+        ]
+    }
+}
+
+/// Moments of `src` as a density, counting only the pixels selected by
+/// `mask`.
+pub fn moments<T: Primitive>(src: &Image<Gray<T>>, mask: Option<&ImageBinary>) -> Moments {
+    check_mask(src, mask);
+    let mut m = Moments::default();
+    for (x, y, p) in src.iter().filter(|&(x, y, _)| selected(mask, x, y)) {
+        let v = p[0].to_f64().unwrap();
+        let (x, y) = (x as f64, y as f64);
+        m.m00 += v;
+        m.m10 += v * x;
+        m.m01 += v * y;
+        m.m20 += v * x * x;
+        m.m11 += v * x * y;
+        m.m02 += v * y * y;
+        m.m30 += v * x * x * x;
+        m.m21 += v * x * x * y;
+        m.m12 += v * x * y * y;
+        m.m03 += v * y * y * y;
+    }
+    if m.m00 == 0f64 {
+        return m;
+    }
+    let (cx, cy) = (m.m10 / m.m00, m.m01 / m.m00);
+    m.mu20 = m.m20 - cx * m.m10;
+    m.mu11 = m.m11 - cx * m.m01;
+    m.mu02 = m.m02 - cy * m.m01;
+    m.mu30 = m.m30 - 3f64 * cx * m.m20 + 2f64 * cx * cx * m.m10;
+    m.mu21 = m.m21 - 2f64 * cx * m.m11 - cy * m.m20 + 2f64 * cx * cx * m.m01;
+    m.mu12 = m.m12 - 2f64 * cy * m.m11 - cx * m.m02 + 2f64 * cy * cy * m.m10;
+    m.mu03 = m.m03 - 3f64 * cy * m.m02 + 2f64 * cy * cy * m.m01;
+    let s2 = m.m00 * m.m00;
+    let s3 = s2 * m.m00.sqrt();
+    m.nu20 = m.mu20 / s2;
+    m.nu11 = m.mu11 / s2;
+    m.nu02 = m.mu02 / s2;
+    m.nu30 = m.mu30 / s3;
+    m.nu21 = m.mu21 / s3;
+    m.nu12 = m.mu12 / s3;
+    m.nu03 = m.mu03 / s3;
+    m
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::ImageGray;
+    use pixel::{bgr, gray};
+    use image::ImageBGR;
+    use transform::rotate_cw90;
+
+    #[test]
+    fn test_stats() {
+        let img = gray_image![1, 2, 3; 4, 0, 6];
+        let mask = gray_image![1, 1, 0; 1, 0, 0];
+        assert_eq!(sum(&img, None), vec![16f64]);
+        assert_eq!(sum(&img, Some(&mask)), vec![7f64]);
+        let (m, s) = mean_std_dev(&img, Some(&mask));
+        assert!((m[0] - 7f64 / 3f64).abs() < 1e-12);
+        assert!((s[0] - (14f64 / 9f64).sqrt()).abs() < 1e-12);
+        assert_eq!(count_non_zero(&img, None), vec![5]);
+
+        let loc = min_max_loc(&img, Some(&mask)).unwrap()[0];
+        assert_eq!((loc.min, loc.max), (1, 4));
+        assert_eq!(loc.max_loc, Point::new(0, 1));
+        let mut none = ImageGray::new(3, 2);
+        none.zero();
+        assert!(min_max_loc(&img, Some(&none)).is_none());
+
+        let mut color = ImageBGR::new(2, 1);
+        color[(0, 0)] = bgr(1, 10, 0);
+        color[(1, 0)] = bgr(3, 20, 5);
+        assert_eq!(mean(&color, None), vec![2f64, 15f64, 2.5]);
+        assert_eq!(norm(&color, NormType::L1, None), 39f64);
+        assert_eq!(norm(&color, NormType::Inf, None), 20f64);
+
+        let other = gray_image![1, 2, 3; 4, 3, 2];
+        assert_eq!(norm_diff(&img, &other, NormType::L2, None).unwrap(), 5f64);
+        let rel = norm_relative(&img, &other, NormType::L1, None).unwrap();
+        assert!((rel - 7f64 / 15f64).abs() < 1e-12);
+        assert!(norm_diff(&img, &gray_image![1], NormType::L1, None).is_err());
+    }
+
+    #[test]
+    fn test_moments() {
+        // a 4x2 block of ones
+        let mut img = ImageGray::new(9, 7);
+        img.zero();
+        for y in 2..4 {
+            for x in 3..7 {
+                img[(x, y)] = gray(1);
+            }
+        }
+        let m = moments(&img, None);
+        assert_eq!(m.m00, 8f64);
+        assert_eq!(m.centroid(), Some((4.5, 2.5)));
+        assert!((m.mu20 - 2f64 * 4f64 * 15f64 / 12f64).abs() < 1e-9);
+        assert!((m.mu02 - 4f64 * 2f64 * 3f64 / 12f64).abs() < 1e-9);
+        assert!(m.mu11.abs() < 1e-9);
+
+        let hu = m.hu();
+        let rotated = moments(&rotate_cw90(&img), None).hu();
+        for (a, b) in hu.iter().zip(rotated.iter()) {
+            assert!((a - b).abs() < 1e-12, "{} {}", a, b);
+        }
+        assert!((hu[0] - (m.nu20 + m.nu02)).abs() < 1e-15);
+
+        // masking out the block leaves no mass
+        let mut mask = ImageGray::new(9, 7);
+        mask.zero();
+        assert_eq!(moments(&img, Some(&mask)).centroid(), None);
+    }
+}
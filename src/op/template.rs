@@ -0,0 +1,146 @@
+use image::{Image, ImageGrayf};
+use pixel::{Pixel, gray};
+use geo::Rect;
+use num::ToPrimitive;
+use op::integral::IntegralImage;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchMethod {
+    /// sum of squared differences, best at the minimum
+    SqDiff,
+    /// `SqDiff` divided by the norms of the window and the template
+    SqDiffNormed,
+    /// cross-correlation, best at the maximum
+    CCorr,
+    /// normalized cross-correlation, in `[0, 1]` for non-negative pixels
+    CCorrNormed,
+    /// zero-mean normalized cross-correlation, in `[-1, 1]`
+    CCoeffNormed,
+}
+
+const EPS: f64 = 1e-12;
+
+/// Slides `templ` over `src` and scores every placement; the score map has
+/// size `(W - w + 1, H - h + 1)` and is indexed by the top-left corner of the
+/// placement. Multichannel scores are summed over channels.
+///
+/// Window sums are taken from integral images, so only the cross term costs
+/// a pass over the template per placement.
+pub fn match_template<P: Pixel>(
+    src: &Image<P>,
+    templ: &Image<P>,
+    method: MatchMethod,
+) -> ImageGrayf {
+    let (tw, th) = templ.size();
+    assert!(tw > 0 && th > 0);
+    assert!(tw <= src.width() && th <= src.height());
+    let width = src.width() - tw + 1;
+    let height = src.height() - th + 1;
+    let n = (tw * th) as f64;
+    let ch = P::channels();
+
+    let sums: Vec<IntegralImage> = (0..ch).map(|c| IntegralImage::new(src, c)).collect();
+    let squares: Vec<IntegralImage> = (0..ch).map(|c| IntegralImage::squared(src, c)).collect();
+    let mut t_sum = vec![0f64; ch];
+    let mut t_sq = 0f64;
+    for (_, _, p) in templ.iter() {
+        for c in 0..ch {
+            let v = p.raw()[c].to_f64().unwrap();
+            t_sum[c] += v;
+            t_sq += v * v;
+        }
+    }
+    let t_var = t_sq - t_sum.iter().map(|s| s * s / n).sum::<f64>();
+
+    let mut dst = ImageGrayf::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let mut cross = 0f64;
+            for j in 0..th {
+                let prow = &src.row(y + j)[x..x + tw];
+                for (a, b) in prow.iter().zip(templ.row(j)) {
+                    for c in 0..ch {
+                        cross += a.raw()[c].to_f64().unwrap() * b.raw()[c].to_f64().unwrap();
+                    }
+                }
+            }
+            let rect = Rect::new(x as isize, y as isize, tw as isize, th as isize);
+            let w_sq: f64 = squares.iter().map(|s| s.sum(rect)).sum();
+            let score = match method {
+                MatchMethod::SqDiff => w_sq - 2f64 * cross + t_sq,
+                MatchMethod::SqDiffNormed => {
+                    let denom = (w_sq * t_sq).sqrt();
+                    let ssd = (w_sq - 2f64 * cross + t_sq).max(0f64);
+                    if denom > EPS {
+                        ssd / denom
+                    } else if ssd > EPS {
+                        1f64
+                    } else {
+                        0f64
+                    }
+                }
+                MatchMethod::CCorr => cross,
+                MatchMethod::CCorrNormed => {
+                    let denom = (w_sq * t_sq).sqrt();
+                    if denom > EPS { cross / denom } else { 0f64 }
+                }
+                MatchMethod::CCoeffNormed => {
+                    let mut num = cross;
+                    let mut w_var = w_sq;
+                    for c in 0..ch {
+                        let w_sum = sums[c].sum(rect);
+                        num -= w_sum * t_sum[c] / n;
+                        w_var -= w_sum * w_sum / n;
+                    }
+                    let denom = (w_var.max(0f64) * t_var.max(0f64)).sqrt();
+                    if denom > EPS {
+                        (num / denom).max(-1f64).min(1f64)
+                    } else {
+                        0f64
+                    }
+                }
+            };
+            dst[(x, y)] = gray(score as f32);
+        }
+    }
+    dst
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use geo::Pointi;
+    use transform::min_max_loc;
+
+    #[test]
+    fn test_match_template() {
+        let img =
+            gray_image![
+            10, 10, 10, 10, 10, 10;
+            10, 10, 10, 10, 10, 10;
+            10, 10, 90, 40, 10, 10;
+            10, 10, 60, 20, 10, 10;
+            10, 10, 10, 10, 10, 10
+        ];
+        let templ =
+            gray_image![
+            90, 40;
+            60, 20
+        ];
+        let expected = Pointi::new(2, 2);
+        for &method in [MatchMethod::SqDiff, MatchMethod::SqDiffNormed].iter() {
+            let scores = match_template(&img, &templ, method);
+            assert_eq!(scores.size(), (5, 4));
+            let loc = min_max_loc(&scores).unwrap()[0];
+            assert_eq!(loc.min_loc, expected);
+            assert!(loc.min.abs() < 1e-3);
+        }
+        for &method in [MatchMethod::CCorrNormed, MatchMethod::CCoeffNormed].iter() {
+            let loc = min_max_loc(&match_template(&img, &templ, method)).unwrap()[0];
+            assert_eq!(loc.max_loc, expected);
+            assert!((loc.max - 1f32).abs() < 1e-3);
+        }
+        let loc = min_max_loc(&match_template(&img, &templ, MatchMethod::CCorr)).unwrap()[0];
+        assert_eq!(loc.max, (90 * 90 + 40 * 40 + 60 * 60 + 20 * 20) as f32);
+    }
+}
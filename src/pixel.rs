@@ -2,7 +2,7 @@ use traits::Primitive;
 use num::{Saturating, NumCast};
 use std::ops::{Index, IndexMut, Add, Sub, Mul};
 use std::fmt::Debug;
-use math::utils::clip_from_f32;
+use math::utils::{clip_from_f32, is_integral};
 
 #[derive(PartialEq, Eq, Clone, Debug, Copy, Hash)]
 #[repr(C, packed)]
@@ -276,6 +276,21 @@ define_colors! {
 
 pub trait AlphaPixel: Pixel {
     fn alpha_index() -> usize;
+
+    /// Alpha of a fully opaque pixel, see `full_scale`.
+    fn opaque() -> f32 {
+        full_scale::<Self::Subpixel>()
+    }
+}
+
+/// Value of a subpixel at full intensity: the maximum for integer types and
+/// 1 for floating point ones.
+pub fn full_scale<T: Primitive>() -> f32 {
+    if is_integral::<T>() {
+        T::max_value().to_f32().unwrap()
+    } else {
+        1f32
+    }
 }
 
 macro_rules! define_alpha(
@@ -0,0 +1,269 @@
+//! Sub-pixel sampling of images.
+//!
+//! Coordinates are in pixel units with integer values at pixel centers.
+//! Positions outside the image are resolved like `Eye` does, according to
+//! the sampler's `AlterType`.
+
+use num::Zero;
+use num::traits::ToPrimitive;
+use eye::{Eye, AlterType};
+use geo::Pointf;
+use image::Image;
+use math::utils::is_integral;
+use pixel::{Pixel, MAX_CHANNEL_COUNT};
+use traits::Primitive;
+use transform::InterplateType;
+
+pub trait Sampler<P: Pixel> {
+    fn sample(&self, img: &Image<P>, at: Pointf) -> P;
+}
+
+/// Keys cubic convolution kernel with a = -0.75.
+pub fn cubic_weight(x: f32) -> f32 {
+    let a = -0.75f32;
+    let x = x.abs();
+    if x <= 1f32 {
+        ((a + 2f32) * x - (a + 3f32)) * x * x + 1f32
+    } else if x < 2f32 {
+        ((a * x - 5f32 * a) * x + 8f32 * a) * x - 4f32 * a
+    } else {
+        0f32
+    }
+}
+
+pub fn lanczos_weight(x: f32, order: f32) -> f32 {
+    use std::f32::consts::PI;
+    if x == 0f32 {
+        1f32
+    } else if x.abs() < order {
+        let px = PI * x;
+        order * px.sin() * (px / order).sin() / (px * px)
+    } else {
+        0f32
+    }
+}
+
+/// Most taps `kernel_taps` returns, for `Lanczos4`.
+pub const MAX_TAPS: usize = 8;
+
+/// Interpolation taps around the fractional coordinate `t`: the index of the
+/// first tap, the normalized weights and how many of them are used.
+pub fn kernel_taps(interp: InterplateType, t: f32) -> (isize, [f32; MAX_TAPS], usize) {
+    let mut weights = [0f32; MAX_TAPS];
+    let radius = match interp {
+        InterplateType::Nearest => {
+            weights[0] = 1f32;
+            return (t.round() as isize, weights, 1);
+        }
+        InterplateType::Bilinear | InterplateType::Area => 1,
+        InterplateType::Bicubic => 2,
+        InterplateType::Lanczos3 => 3,
+        InterplateType::Lanczos4 => 4,
+    };
+    let n = 2 * radius as usize;
+    let start = t.floor() as isize - (radius - 1);
+    let mut sum = 0f32;
+    for k in 0..n {
+        let d = t - (start + k as isize) as f32;
+        weights[k] = match interp {
+            InterplateType::Bicubic => cubic_weight(d),
+            InterplateType::Lanczos3 => lanczos_weight(d, 3f32),
+            InterplateType::Lanczos4 => lanczos_weight(d, 4f32),
+            _ => 1f32 - d.abs(),
+        };
+        sum += weights[k];
+    }
+    weights[..n].iter_mut().for_each(|w| *w /= sum);
+    (start, weights, n)
+}
+
+/// Rounds for integral subpixels, then saturates to the subpixel range.
+/// NaN becomes zero.
+pub fn saturate<T: Primitive>(v: f32) -> T {
+    if v.is_nan() {
+        return T::zero();
+    }
+    let v = if is_integral::<T>() { v.round() } else { v };
+    // the range check happens in `T`, as the f32 of a wide integer maximum
+    // can lie past it
+    T::from(v).unwrap_or(if v > 0f32 { T::max_value() } else { T::min_value() })
+}
+
+// weighted sum over the separable kernel of `interp`; `eye` is only asked
+// for taps at the border
+fn convolve<P: Pixel>(img: &Image<P>, at: Pointf, eye: Eye<P>, interp: InterplateType) -> P {
+    let (x0, wx, nx) = kernel_taps(interp, at.x);
+    let (y0, wy, ny) = kernel_taps(interp, at.y);
+    let inside = x0 >= 0 && y0 >= 0 && x0 + nx as isize <= img.width() as isize &&
+        y0 + ny as isize <= img.height() as isize;
+    let ch = P::channels();
+    let mut acc = [0f32; MAX_CHANNEL_COUNT];
+    for (j, &w1) in wy[..ny].iter().enumerate() {
+        let y = y0 + j as isize;
+        for (i, &w0) in wx[..nx].iter().enumerate() {
+            let x = x0 + i as isize;
+            let p = if inside {
+                img[(x as usize, y as usize)]
+            } else {
+                eye.x(x).y(y).look(img)
+            };
+            for c in 0..ch {
+                acc[c] += p.raw()[c].to_f32().unwrap() * w0 * w1;
+            }
+        }
+    }
+    let mut out = [P::Subpixel::zero(); MAX_CHANNEL_COUNT];
+    for c in 0..ch {
+        out[c] = saturate(acc[c]);
+    }
+    P::from_raw(&out[..ch])
+}
+
+macro_rules! define_border_methods {
+    () => (
+    pub fn constant(mut self, pixel: P) -> Self {
+        self.border = AlterType::Constant(pixel);
+        self
+    }
+
+    pub fn mirror(mut self) -> Self {
+        self.border = AlterType::Mirror;
+        self
+    }
+
+    pub fn extend(mut self) -> Self {
+        self.border = AlterType::Extend;
+        self
+    }
+
+    pub fn alter(mut self, border: AlterType<P>) -> Self {
+        self.border = border;
+        self
+    }
+
+    fn eye(&self) -> Eye<P> {
+        Eye::default().alter(self.border)
+    }
+    )
+}
+
+macro_rules! define_sampler {
+    ($name:ident) => (
+#[derive(Debug, Clone, Copy)]
+pub struct $name<P: Pixel> {
+    border: AlterType<P>,
+}
+
+impl<P: Pixel> $name<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    define_border_methods!();
+}
+
+impl<P: Pixel> Default for $name<P> {
+    fn default() -> Self {
+        $name { border: AlterType::Extend }
+    }
+}
+    )
+}
+
+define_sampler!(NearestSampler);
+define_sampler!(BilinearSampler);
+define_sampler!(BicubicSampler);
+
+impl<P: Pixel> Sampler<P> for NearestSampler<P> {
+    fn sample(&self, img: &Image<P>, at: Pointf) -> P {
+        self.eye()
+            .x(at.x.round() as isize)
+            .y(at.y.round() as isize)
+            .look(img)
+    }
+}
+
+impl<P: Pixel> Sampler<P> for BilinearSampler<P> {
+    fn sample(&self, img: &Image<P>, at: Pointf) -> P {
+        convolve(img, at, self.eye(), InterplateType::Bilinear)
+    }
+}
+
+impl<P: Pixel> Sampler<P> for BicubicSampler<P> {
+    fn sample(&self, img: &Image<P>, at: Pointf) -> P {
+        convolve(img, at, self.eye(), InterplateType::Bicubic)
+    }
+}
+
+/// Lanczos windowed sinc of order 3 or 4.
+#[derive(Debug, Clone, Copy)]
+pub struct LanczosSampler<P: Pixel> {
+    border: AlterType<P>,
+    order: usize,
+}
+
+impl<P: Pixel> LanczosSampler<P> {
+    pub fn new(order: usize) -> Self {
+        assert!(order == 3 || order == 4);
+        LanczosSampler {
+            border: AlterType::Extend,
+            order: order,
+        }
+    }
+
+    define_border_methods!();
+}
+
+impl<P: Pixel> Sampler<P> for LanczosSampler<P> {
+    fn sample(&self, img: &Image<P>, at: Pointf) -> P {
+        let interp = if self.order == 3 {
+            InterplateType::Lanczos3
+        } else {
+            InterplateType::Lanczos4
+        };
+        convolve(img, at, self.eye(), interp)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use geo::Point;
+    use pixel::gray;
+
+    #[test]
+    fn test_samplers() {
+        let img =
+            gray_image![
+            0, 10, 20;
+            30, 40, 50;
+            60, 70, 80
+        ];
+        let at = Point::new(0.5f32, 1.5f32);
+        assert_eq!(NearestSampler::new().sample(&img, Point::new(0.4, 1.6)), gray(60));
+        assert_eq!(BilinearSampler::new().sample(&img, at), gray(50));
+        // rounded rather than truncated, like the other samplers
+        assert_eq!(BilinearSampler::new().sample(&img, Point::new(0.25, 0f32)), gray(3));
+        assert_eq!(BicubicSampler::new().sample(&img, Point::new(1f32, 1f32)), gray(40));
+        assert_eq!(LanczosSampler::new(3).sample(&img, Point::new(2f32, 0f32)), gray(20));
+
+        let outside = Point::new(-2f32, 1f32);
+        assert_eq!(BilinearSampler::new().sample(&img, outside), gray(30));
+        assert_eq!(BilinearSampler::new().mirror().sample(&img, outside), gray(50));
+        assert_eq!(
+            BicubicSampler::new().constant(gray(7)).sample(&img, Point::new(-5f32, 0f32)),
+            gray(7)
+        );
+    }
+
+    #[test]
+    fn test_saturate() {
+        assert_eq!(saturate::<u8>(254.5), 255u8);
+        assert_eq!(saturate::<u8>(-3f32), 0u8);
+        assert_eq!(saturate::<u32>(5e9), u32::max_value());
+        assert_eq!(saturate::<i32>(-5e9), i32::min_value());
+        assert_eq!(saturate::<u8>(::std::f32::NAN), 0u8);
+        assert_eq!(saturate::<f32>(::std::f32::NAN), 0f32);
+        assert_eq!(saturate::<f32>(1.25), 1.25f32);
+    }
+}
@@ -0,0 +1,199 @@
+use image::ImageBinary;
+use geo::{Point, Pointi, Recti};
+use geo::polygon::bounding_rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderType {
+    /// border between a component and the background surrounding it
+    Outer,
+    /// border between a component and a hole inside it
+    Hole,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    pub points: Vec<Pointi>,
+    pub border_type: BorderType,
+    /// index of the enclosing contour in the list returned by `find_contours`
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+impl Contour {
+    pub fn bounding_rect(&self) -> Recti {
+        bounding_rect(&self.points)
+    }
+}
+
+// 8-neighbourhood in clockwise order (y axis pointing down)
+const DIRS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+fn dir_index(from: (isize, isize), to: (isize, isize)) -> usize {
+    let d = (to.0 - from.0, to.1 - from.1);
+    DIRS.iter().position(|&x| x == d).unwrap()
+}
+
+/// Finds the borders of the non-zero regions of `src` with the border
+/// following algorithm of Suzuki and Abe (1985).
+///
+/// Contours are listed in the order their first pixel is met in a raster
+/// scan. As displayed with the y axis pointing down, points of outer borders
+/// run counterclockwise and points of hole borders clockwise.
+pub fn find_contours(src: &ImageBinary) -> Vec<Contour> {
+    // pad by one pixel so the followers never leave the buffer
+    let w = src.width() as isize + 2;
+    let h = src.height() as isize + 2;
+    let mut f = vec![0i32; (w * h) as usize];
+    for (x, y, p) in src.iter() {
+        if p[0] != 0 {
+            f[((y as isize + 1) * w + x as isize + 1) as usize] = 1;
+        }
+    }
+    let idx = |x: isize, y: isize| (y * w + x) as usize;
+
+    let mut contours: Vec<Contour> = Vec::new();
+    // NBD 1 is the frame; border with NBD `k` is contours[k - 2]
+    let mut nbd = 1i32;
+
+    for y in 1..h - 1 {
+        let mut lnbd = 1i32;
+        for x in 1..w - 1 {
+            let cur = f[idx(x, y)];
+            if cur == 0 {
+                continue;
+            }
+            let start = if cur == 1 && f[idx(x - 1, y)] == 0 {
+                Some((BorderType::Outer, (x - 1, y)))
+            } else if cur >= 1 && f[idx(x + 1, y)] == 0 {
+                if cur > 1 {
+                    lnbd = cur;
+                }
+                Some((BorderType::Hole, (x + 1, y)))
+            } else {
+                None
+            };
+
+            if let Some((border_type, from)) = start {
+                nbd += 1;
+                let parent = if lnbd <= 1 {
+                    None
+                } else {
+                    let prev = &contours[(lnbd - 2) as usize];
+                    if prev.border_type == border_type {
+                        prev.parent
+                    } else {
+                        Some((lnbd - 2) as usize)
+                    }
+                };
+
+                let mut points = Vec::new();
+                let p0 = (x, y);
+                // look clockwise for the first non-zero neighbour
+                let d0 = dir_index(p0, from);
+                let first = (0..8)
+                    .map(|k| DIRS[(d0 + k) % 8])
+                    .map(|(dx, dy)| (x + dx, y + dy))
+                    .find(|&(nx, ny)| f[idx(nx, ny)] != 0);
+                match first {
+                    None => {
+                        // isolated pixel
+                        f[idx(x, y)] = -nbd;
+                        points.push(Point::new(x - 1, y - 1));
+                    }
+                    Some(p1) => {
+                        let mut p2 = p1;
+                        let mut p3 = p0;
+                        loop {
+                            points.push(Point::new(p3.0 - 1, p3.1 - 1));
+                            // look counterclockwise, starting next to p2
+                            let d = dir_index(p3, p2);
+                            let mut east_zero = false;
+                            let mut p4 = p3;
+                            for k in 1..9 {
+                                let (dx, dy) = DIRS[(d + 8 - k) % 8];
+                                let q = (p3.0 + dx, p3.1 + dy);
+                                if f[idx(q.0, q.1)] != 0 {
+                                    p4 = q;
+                                    break;
+                                }
+                                if (dx, dy) == (1, 0) {
+                                    east_zero = true;
+                                }
+                            }
+                            if east_zero {
+                                f[idx(p3.0, p3.1)] = -nbd;
+                            } else if f[idx(p3.0, p3.1)] == 1 {
+                                f[idx(p3.0, p3.1)] = nbd;
+                            }
+                            if p4 == p0 && p3 == p1 {
+                                break;
+                            }
+                            p2 = p3;
+                            p3 = p4;
+                        }
+                    }
+                }
+
+                let index = contours.len();
+                if let Some(i) = parent {
+                    contours[i].children.push(index);
+                }
+                contours.push(Contour {
+                    points: points,
+                    border_type: border_type,
+                    parent: parent,
+                    children: Vec::new(),
+                });
+            }
+
+            let cur = f[idx(x, y)];
+            if cur != 1 {
+                lnbd = cur.abs();
+            }
+        }
+    }
+    contours
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_contours() {
+        let img =
+            gray_image![
+            0, 0, 0, 0, 0, 0, 0;
+            0, 1, 1, 1, 1, 0, 0;
+            0, 1, 0, 0, 1, 0, 0;
+            0, 1, 1, 1, 1, 0, 1;
+            0, 0, 0, 0, 0, 0, 0
+        ];
+        let contours = find_contours(&img);
+        assert_eq!(contours.len(), 3);
+
+        assert_eq!(contours[0].border_type, BorderType::Outer);
+        assert_eq!(contours[0].parent, None);
+        assert_eq!(contours[0].children, vec![1]);
+        assert_eq!(contours[0].points.len(), 10);
+        assert_eq!(contours[0].points[0], Pointi::new(1, 1));
+        assert_eq!(contours[0].points[1], Pointi::new(1, 2));
+        assert_eq!(contours[0].bounding_rect(), Recti::new(1, 1, 4, 3));
+
+        assert_eq!(contours[1].border_type, BorderType::Hole);
+        assert_eq!(contours[1].parent, Some(0));
+
+        assert_eq!(contours[2].border_type, BorderType::Outer);
+        assert_eq!(contours[2].parent, None);
+        assert_eq!(contours[2].points, vec![Pointi::new(6, 3)]);
+    }
+}
@@ -0,0 +1,209 @@
+use std::f32;
+use image::{ImageBinary, ImageGrayf, ImageLabel};
+use pixel::gray;
+use segment::{Connectivity, connected_components};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceType {
+    /// exact Euclidean distance
+    Euclidean,
+    /// city block distance, `|dx| + |dy|`
+    L1,
+    /// chessboard distance, `max(|dx|, |dy|)`
+    LInf,
+}
+
+// stands in for infinity in the Euclidean transform
+const FAR: f32 = 1e20;
+
+// 1-D squared distance transform of the sampled function `f` (Felzenszwalb
+// and Huttenlocher, 2012); `arg[q]` receives the index of the minimizing
+// sample
+fn edt_1d(f: &[f32], d: &mut [f32], arg: &mut [usize], v: &mut [usize], z: &mut [f32]) {
+    let n = f.len();
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = f32::NEG_INFINITY;
+    z[1] = f32::INFINITY;
+    for q in 1..n {
+        let mut s;
+        loop {
+            let p = v[k];
+            s = ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2 * (q - p)) as f32;
+            if s <= z[k] && k > 0 {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+        if s <= z[k] {
+            v[k] = q;
+        } else {
+            k += 1;
+            v[k] = q;
+            z[k] = s;
+        }
+        z[k + 1] = f32::INFINITY;
+    }
+    k = 0;
+    for q in 0..n {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+        let p = v[k];
+        let dq = q as f32 - p as f32;
+        d[q] = dq * dq + f[p];
+        arg[q] = p;
+    }
+}
+
+// distances and raster index of the nearest zero pixel, if any
+fn transform(src: &ImageBinary, dist: DistanceType) -> (Vec<f32>, Vec<usize>) {
+    let (w, h) = src.size();
+    let n = w * h;
+    let far = if dist == DistanceType::Euclidean { FAR } else { f32::INFINITY };
+    let mut d = vec![far; n];
+    let mut nearest = vec![usize::max_value(); n];
+    for (x, y, p) in src.iter() {
+        if p[0] == 0 {
+            d[y * w + x] = 0f32;
+            nearest[y * w + x] = y * w + x;
+        }
+    }
+    if dist == DistanceType::Euclidean {
+        let len = w.max(h);
+        let (mut f, mut out) = (vec![0f32; len], vec![0f32; len]);
+        let (mut arg, mut v) = (vec![0usize; len], vec![0usize; len]);
+        let mut z = vec![0f32; len + 1];
+        let mut row_of = vec![0usize; n];
+        // columns
+        for x in 0..w {
+            for y in 0..h {
+                f[y] = d[y * w + x];
+            }
+            edt_1d(&f[..h], &mut out[..h], &mut arg[..h], &mut v, &mut z);
+            for y in 0..h {
+                d[y * w + x] = out[y];
+                row_of[y * w + x] = arg[y];
+            }
+        }
+        // rows
+        for y in 0..h {
+            f[..w].copy_from_slice(&d[y * w..(y + 1) * w]);
+            edt_1d(&f[..w], &mut out[..w], &mut arg[..w], &mut v, &mut z);
+            for x in 0..w {
+                let i = y * w + x;
+                if out[x] >= FAR {
+                    d[i] = f32::INFINITY;
+                } else {
+                    d[i] = out[x].sqrt();
+                    let col = arg[x];
+                    let row = row_of[y * w + col];
+                    nearest[i] = row * w + col;
+                }
+            }
+        }
+    } else {
+        // two-pass chamfer propagation, exact for these metrics
+        let diagonal = dist == DistanceType::LInf;
+        let forward: &[(isize, isize)] = if diagonal {
+            &[(-1, 0), (-1, -1), (0, -1), (1, -1)]
+        } else {
+            &[(-1, 0), (0, -1)]
+        };
+        for pass in 0..2 {
+            for k in 0..n {
+                let i = if pass == 0 { k } else { n - 1 - k };
+                let (x, y) = ((i % w) as isize, (i / w) as isize);
+                for &(dx, dy) in forward.iter() {
+                    let (dx, dy) = if pass == 0 { (dx, dy) } else { (-dx, -dy) };
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= w as isize || ny >= h as isize {
+                        continue;
+                    }
+                    let j = ny as usize * w + nx as usize;
+                    if d[j] + 1f32 < d[i] {
+                        d[i] = d[j] + 1f32;
+                        nearest[i] = nearest[j];
+                    }
+                }
+            }
+        }
+    }
+    (d, nearest)
+}
+
+/// Distance from every pixel to the nearest zero pixel of `src`; zero pixels
+/// get 0, and every pixel gets infinity if there is no zero pixel.
+pub fn distance_transform(src: &ImageBinary, dist: DistanceType) -> ImageGrayf {
+    let (d, _) = transform(src, dist);
+    let mut dst = ImageGrayf::new(src.width(), src.height());
+    for (x, y, p) in dst.iter_mut() {
+        *p = gray(d[y * src.width() + x]);
+    }
+    dst
+}
+
+/// Like `distance_transform`, also labelling every pixel with the
+/// 8-connected component of zero pixels its nearest zero pixel belongs to.
+/// Components are numbered as by `connected_components`; pixels with no
+/// zero pixel to go to get label 0.
+pub fn distance_transform_with_labels(
+    src: &ImageBinary,
+    dist: DistanceType,
+) -> (ImageGrayf, ImageLabel) {
+    let (w, h) = src.size();
+    let (d, nearest) = transform(src, dist);
+    let mut background = ImageBinary::new(w, h);
+    for (x, y, p) in background.iter_mut() {
+        *p = gray(if src[(x, y)][0] == 0 { 1 } else { 0 });
+    }
+    let (components, _) = connected_components(&background, Connectivity::Eight);
+
+    let mut dst = ImageGrayf::new(w, h);
+    let mut labels = ImageLabel::new(w, h);
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            dst[(x, y)] = gray(d[i]);
+            labels[(x, y)] = if nearest[i] == usize::max_value() {
+                gray(0)
+            } else {
+                components[(nearest[i] % w, nearest[i] / w)]
+            };
+        }
+    }
+    (dst, labels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_distance_transform() {
+        let img =
+            gray_image![
+            0, 1, 1, 1, 1, 1;
+            1, 1, 1, 1, 1, 1;
+            1, 1, 1, 1, 1, 0
+        ];
+        let d = distance_transform(&img, DistanceType::Euclidean);
+        assert_eq!(d[(0, 0)][0], 0f32);
+        assert_eq!(d[(2, 0)][0], 2f32);
+        assert_eq!(d[(1, 1)][0], 2f32.sqrt());
+        assert!((d[(3, 1)][0] - 5f32.sqrt()).abs() < 1e-6);
+        assert_eq!(d[(0, 2)][0], 2f32);
+        assert_eq!(distance_transform(&img, DistanceType::L1)[(3, 1)][0], 3f32);
+        assert_eq!(distance_transform(&img, DistanceType::LInf)[(3, 1)][0], 2f32);
+
+        let (_, labels) = distance_transform_with_labels(&img, DistanceType::Euclidean);
+        assert_eq!(labels[(1, 0)][0], 1);
+        assert_eq!(labels[(4, 1)][0], 2);
+        assert_eq!(labels[(5, 2)][0], 2);
+
+        let ones = gray_image![1, 1; 1, 1];
+        let d = distance_transform(&ones, DistanceType::L1);
+        assert!(d.iter().all(|(_, _, p)| p[0] == f32::INFINITY));
+    }
+}
@@ -0,0 +1,275 @@
+use image::{ImageBinary, ImageLabel};
+use pixel::Gray;
+use geo::{Point, Pointf, Rect, Recti};
+use segment::Connectivity;
+
+/// Statistics of one connected component. Component `label` occupies the
+/// pixels equal to `label` in the label image; `0` is the background.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentStats {
+    pub label: u32,
+    pub area: usize,
+    pub bbox: Recti,
+    pub centroid: Pointf,
+    /// number of pixel edges shared with the background or the image border
+    pub perimeter: usize,
+}
+
+fn find(parent: &mut Vec<u32>, x: u32) -> u32 {
+    let mut root = x;
+    while parent[root as usize] != root {
+        root = parent[root as usize];
+    }
+    // path compression
+    let mut x = x;
+    while parent[x as usize] != root {
+        let next = parent[x as usize];
+        parent[x as usize] = root;
+        x = next;
+    }
+    root
+}
+
+fn union(parent: &mut Vec<u32>, a: u32, b: u32) -> u32 {
+    let ra = find(parent, a);
+    let rb = find(parent, b);
+    if ra < rb {
+        parent[rb as usize] = ra;
+        ra
+    } else {
+        parent[ra as usize] = rb;
+        rb
+    }
+}
+
+/// Labels the non-zero pixels of `src` with a two-pass union-find scan.
+///
+/// Returns the label image and the number of components; labels are
+/// consecutive, starting from 1 in raster order.
+pub fn connected_components(src: &ImageBinary, conn: Connectivity) -> (ImageLabel, usize) {
+    let (w, h) = src.size();
+    let mut labels = ImageLabel::new(w, h);
+    labels.zero();
+    // parent[0] is the background
+    let mut parent: Vec<u32> = vec![0];
+
+    for y in 0..h {
+        for x in 0..w {
+            if src[(x, y)][0] == 0 {
+                continue;
+            }
+            let mut cur = 0u32;
+            {
+                let mut visit = |nx: isize, ny: isize, cur: &mut u32| {
+                    if nx < 0 || ny < 0 || nx >= w as isize {
+                        return;
+                    }
+                    let l = labels[(nx as usize, ny as usize)][0];
+                    if l == 0 {
+                        return;
+                    }
+                    *cur = if *cur == 0 { l } else { union(&mut parent, *cur, l) };
+                };
+                let (x, y) = (x as isize, y as isize);
+                visit(x - 1, y, &mut cur);
+                visit(x, y - 1, &mut cur);
+                if conn == Connectivity::Eight {
+                    visit(x - 1, y - 1, &mut cur);
+                    visit(x + 1, y - 1, &mut cur);
+                }
+            }
+            if cur == 0 {
+                cur = parent.len() as u32;
+                parent.push(cur);
+            }
+            labels[(x, y)] = Gray([cur]);
+        }
+    }
+
+    // flatten the equivalence classes into consecutive labels
+    let mut remap = vec![0u32; parent.len()];
+    let mut count = 0;
+    for l in 1..parent.len() {
+        let root = find(&mut parent, l as u32) as usize;
+        if remap[root] == 0 {
+            count += 1;
+            remap[root] = count;
+        }
+        remap[l] = remap[root];
+    }
+    labels.iter_mut().for_each(|(_, _, p)| p[0] = remap[p[0] as usize]);
+    (labels, count as usize)
+}
+
+/// Computes area, bounding box, centroid and perimeter of labels `1..=count`.
+pub fn component_stats(labels: &ImageLabel, count: usize) -> Vec<ComponentStats> {
+    let (w, h) = labels.size();
+    let mut min_x = vec![isize::max_value(); count];
+    let mut min_y = vec![isize::max_value(); count];
+    let mut max_x = vec![isize::min_value(); count];
+    let mut max_y = vec![isize::min_value(); count];
+    let mut area = vec![0usize; count];
+    let mut perimeter = vec![0usize; count];
+    let mut sum_x = vec![0f64; count];
+    let mut sum_y = vec![0f64; count];
+
+    for (x, y, p) in labels.iter() {
+        let l = p[0];
+        if l == 0 {
+            continue;
+        }
+        let i = (l - 1) as usize;
+        let (ix, iy) = (x as isize, y as isize);
+        area[i] += 1;
+        sum_x[i] += x as f64;
+        sum_y[i] += y as f64;
+        min_x[i] = min_x[i].min(ix);
+        min_y[i] = min_y[i].min(iy);
+        max_x[i] = max_x[i].max(ix);
+        max_y[i] = max_y[i].max(iy);
+        for &(dx, dy) in Connectivity::Four.offsets() {
+            let (nx, ny) = (ix + dx, iy + dy);
+            if nx < 0 || ny < 0 || nx >= w as isize || ny >= h as isize ||
+                labels[(nx as usize, ny as usize)][0] != l
+            {
+                perimeter[i] += 1;
+            }
+        }
+    }
+
+    (0..count)
+        .map(|i| {
+            ComponentStats {
+                label: i as u32 + 1,
+                area: area[i],
+                bbox: Rect::new(
+                    min_x[i],
+                    min_y[i],
+                    max_x[i] - min_x[i] + 1,
+                    max_y[i] - min_y[i] + 1,
+                ),
+                centroid: Point::new(
+                    (sum_x[i] / area[i] as f64) as f32,
+                    (sum_y[i] / area[i] as f64) as f32,
+                ),
+                perimeter: perimeter[i],
+            }
+        })
+        .collect()
+}
+
+/// Bounding boxes of labels `1..=count`; absent labels get an empty
+/// rectangle at the origin.
+pub fn region_bounds(labels: &ImageLabel, count: usize) -> Vec<Recti> {
+    let mut bounds: Vec<Option<(isize, isize, isize, isize)>> = vec![None; count];
+    for (x, y, p) in labels.iter() {
+        let l = p[0] as usize;
+        if l == 0 || l > count {
+            continue;
+        }
+        let (x, y) = (x as isize, y as isize);
+        bounds[l - 1] = Some(match bounds[l - 1] {
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+    bounds
+        .iter()
+        .map(|b| match *b {
+            Some((x0, y0, x1, y1)) => Rect::new(x0, y0, x1 - x0 + 1, y1 - y0 + 1),
+            None => Rect::new(0, 0, 0, 0),
+        })
+        .collect()
+}
+
+pub fn connected_components_with_stats(
+    src: &ImageBinary,
+    conn: Connectivity,
+) -> (ImageLabel, Vec<ComponentStats>) {
+    let (labels, count) = connected_components(src, conn);
+    let stats = component_stats(&labels, count);
+    (labels, stats)
+}
+
+/// Erases components smaller than `min_area` from `labels` and renumbers the
+/// remaining ones consecutively. Returns the stats of the kept components.
+pub fn remove_small_components(
+    labels: &mut ImageLabel,
+    stats: &[ComponentStats],
+    min_area: usize,
+) -> Vec<ComponentStats> {
+    let mut remap = vec![0u32; stats.len() + 1];
+    let mut kept = Vec::new();
+    for s in stats.iter().filter(|s| s.area >= min_area) {
+        let mut s = s.clone();
+        remap[s.label as usize] = kept.len() as u32 + 1;
+        s.label = kept.len() as u32 + 1;
+        kept.push(s);
+    }
+    labels.iter_mut().for_each(|(_, _, p)| p[0] = remap[p[0] as usize]);
+    kept
+}
+
+/// Clears the connected components of `src` with fewer than `min_area` pixels.
+pub fn remove_small_objects(src: &ImageBinary, conn: Connectivity, min_area: usize) -> ImageBinary {
+    let (labels, stats) = connected_components_with_stats(src, conn);
+    let mut ret = ImageBinary::new(src.width(), src.height());
+    for ((_, _, p), (_, _, l)) in ret.iter_mut().zip(labels.iter()) {
+        let keep = l[0] != 0 && stats[(l[0] - 1) as usize].area >= min_area;
+        *p = Gray([keep as u8]);
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use segment::Connectivity;
+
+    #[test]
+    fn test_connected_components() {
+        let img =
+            gray_image![
+            1, 1, 0, 0, 1;
+            0, 1, 0, 1, 1;
+            0, 0, 1, 0, 0;
+            1, 0, 0, 0, 1
+        ];
+        let (_, n) = connected_components(&img, Connectivity::Four);
+        assert_eq!(n, 5);
+        let (labels, n) = connected_components(&img, Connectivity::Eight);
+        assert_eq!(n, 3);
+        assert_eq!(labels[(0, 0)], labels[(4, 0)]);
+        assert_eq!(labels[(2, 2)], labels[(3, 1)]);
+        assert_eq!(labels[(0, 3)][0], 2);
+        assert_eq!(labels[(4, 3)][0], 3);
+        assert_eq!(labels[(2, 0)][0], 0);
+    }
+
+    #[test]
+    fn test_component_stats() {
+        let img =
+            gray_image![
+            0, 0, 0, 0, 0;
+            0, 1, 1, 1, 0;
+            0, 1, 1, 1, 0;
+            0, 0, 0, 0, 1
+        ];
+        let (mut labels, stats) = connected_components_with_stats(&img, Connectivity::Four);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].area, 6);
+        assert_eq!(stats[0].bbox, Recti::new(1, 1, 3, 2));
+        assert_eq!(stats[0].centroid, Pointf::new(2.0, 1.5));
+        assert_eq!(stats[0].perimeter, 10);
+        assert_eq!(stats[1].perimeter, 4);
+
+        let kept = remove_small_components(&mut labels, &stats, 2);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(labels[(4, 3)][0], 0);
+        assert_eq!(labels[(2, 2)][0], 1);
+
+        let out = remove_small_objects(&img, Connectivity::Four, 2);
+        assert_eq!(out[(4, 3)][0], 0);
+        assert_eq!(out[(1, 1)][0], 1);
+    }
+}
@@ -0,0 +1,42 @@
+pub mod label;
+pub mod contour;
+pub mod distance;
+pub mod thinning;
+pub mod region;
+pub mod slic;
+
+pub use self::label::{ComponentStats, connected_components, connected_components_with_stats,
+                      component_stats, region_bounds, remove_small_components,
+                      remove_small_objects};
+pub use self::contour::{BorderType, Contour, find_contours};
+pub use self::distance::{DistanceType, distance_transform, distance_transform_with_labels};
+pub use self::thinning::{ThinningType, thin};
+pub use self::region::{watershed, region_grow, flood_fill};
+pub use self::slic::{Slic, Superpixel, draw_boundaries};
+
+/// Pixel neighbourhood used when deciding whether two pixels touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    /// Offsets of the neighbours, 4-neighbours first.
+    pub fn offsets(&self) -> &'static [(isize, isize)] {
+        const EIGHT: [(isize, isize); 8] = [
+            (1, 0),
+            (0, 1),
+            (-1, 0),
+            (0, -1),
+            (1, 1),
+            (-1, 1),
+            (-1, -1),
+            (1, -1),
+        ];
+        match *self {
+            Connectivity::Four => &EIGHT[..4],
+            Connectivity::Eight => &EIGHT[..],
+        }
+    }
+}
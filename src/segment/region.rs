@@ -0,0 +1,313 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use image::{Image, ImageLabel};
+use pixel::{Pixel, Gray};
+use traits::Primitive;
+use geo::{Rect, Recti, Pointi};
+use num::ToPrimitive;
+use segment::Connectivity;
+use segment::label::region_bounds;
+
+// queue entry popped lowest `key` first, then first in first out
+#[derive(Debug, PartialEq)]
+struct Queued {
+    key: f32,
+    seq: usize,
+    x: usize,
+    y: usize,
+    label: u32,
+}
+
+impl Eq for Queued {}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Queued) -> Ordering {
+        other
+            .key
+            .partial_cmp(&self.key)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Queued) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn neighbours(x: usize, y: usize, w: usize, h: usize, conn: Connectivity) -> Vec<(usize, usize)> {
+    conn.offsets()
+        .iter()
+        .map(|&(dx, dy)| (x as isize + dx, y as isize + dy))
+        .filter(|&(nx, ny)| nx >= 0 && ny >= 0 && nx < w as isize && ny < h as isize)
+        .map(|(nx, ny)| (nx as usize, ny as usize))
+        .collect()
+}
+
+fn max_label(labels: &ImageLabel) -> usize {
+    labels.iter().map(|(_, _, p)| p[0]).max().unwrap_or(0) as usize
+}
+
+/// Marker-controlled watershed by priority flooding (Meyer, 1991).
+///
+/// The non-zero pixels of `markers` are the seeds of their regions; every
+/// other pixel reachable from a seed is flooded in increasing order of its
+/// value in `img`, usually a gradient magnitude, and takes the label of the
+/// region reaching it first. No watershed lines are drawn. Returns the labels
+/// and the bounds of regions `1..=max marker label`.
+pub fn watershed<T: Primitive>(
+    img: &Image<Gray<T>>,
+    markers: &ImageLabel,
+    conn: Connectivity,
+) -> (ImageLabel, Vec<Recti>) {
+    let (w, h) = img.size();
+    assert_eq!(markers.size(), (w, h));
+    let mut labels = markers.clone();
+    let mut queue = BinaryHeap::new();
+    let mut queued = vec![false; w * h];
+    let mut seq = 0;
+    for (x, y, p) in markers.iter() {
+        if p[0] == 0 {
+            continue;
+        }
+        queued[y * w + x] = true;
+        for (nx, ny) in neighbours(x, y, w, h, conn) {
+            if markers[(nx, ny)][0] == 0 && !queued[ny * w + nx] {
+                queued[ny * w + nx] = true;
+                queue.push(Queued {
+                    key: img[(nx, ny)][0].to_f32().unwrap(),
+                    seq: seq,
+                    x: nx,
+                    y: ny,
+                    label: p[0],
+                });
+                seq += 1;
+            }
+        }
+    }
+    while let Some(q) = queue.pop() {
+        labels[(q.x, q.y)] = Gray([q.label]);
+        for (nx, ny) in neighbours(q.x, q.y, w, h, conn) {
+            if !queued[ny * w + nx] {
+                queued[ny * w + nx] = true;
+                // a pixel is never flooded below the level that reached it
+                let key = img[(nx, ny)][0].to_f32().unwrap().max(q.key);
+                queue.push(Queued {
+                    key: key,
+                    seq: seq,
+                    x: nx,
+                    y: ny,
+                    label: q.label,
+                });
+                seq += 1;
+            }
+        }
+    }
+    let bounds = region_bounds(&labels, max_label(markers));
+    (labels, bounds)
+}
+
+fn channel_values<P: Pixel>(p: &P, out: &mut [f32]) {
+    for c in 0..P::channels() {
+        out[c] = p.raw()[c].to_f32().unwrap();
+    }
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt()
+}
+
+/// Seeded region growing (Adams and Bischof, 1994).
+///
+/// Regions start from the non-zero pixels of `seeds` and repeatedly absorb
+/// the neighbouring pixel closest to their mean color. Pixels farther than
+/// `tolerance` from every adjacent region mean stay unlabelled. Returns the
+/// labels and the bounds of regions `1..=max seed label`.
+pub fn region_grow<P: Pixel>(
+    img: &Image<P>,
+    seeds: &ImageLabel,
+    tolerance: f32,
+    conn: Connectivity,
+) -> (ImageLabel, Vec<Recti>) {
+    let (w, h) = img.size();
+    assert_eq!(seeds.size(), (w, h));
+    let ch = P::channels();
+    let count = max_label(seeds);
+    let mut sums = vec![0f32; count * ch];
+    let mut areas = vec![0usize; count];
+    let mut v = vec![0f32; ch];
+    for (x, y, p) in seeds.iter() {
+        if p[0] != 0 {
+            let l = p[0] as usize - 1;
+            channel_values(&img[(x, y)], &mut v);
+            for c in 0..ch {
+                sums[l * ch + c] += v[c];
+            }
+            areas[l] += 1;
+        }
+    }
+
+    let mut labels = seeds.clone();
+    let mut queue = BinaryHeap::new();
+    let mut seq = 0;
+    let mut mean = vec![0f32; ch];
+    let mut pending: Vec<(usize, usize, u32)> = seeds
+        .iter()
+        .filter(|&(_, _, p)| p[0] != 0)
+        .map(|(x, y, p)| (x, y, p[0]))
+        .collect();
+    loop {
+        // queue the unlabelled neighbours of the newly labelled pixels,
+        // keyed by their distance to the mean of the region
+        for &(x, y, l) in pending.iter() {
+            let li = l as usize - 1;
+            for c in 0..ch {
+                mean[c] = sums[li * ch + c] / areas[li] as f32;
+            }
+            for (nx, ny) in neighbours(x, y, w, h, conn) {
+                if labels[(nx, ny)][0] == 0 {
+                    channel_values(&img[(nx, ny)], &mut v);
+                    queue.push(Queued {
+                        key: distance(&v, &mean),
+                        seq: seq,
+                        x: nx,
+                        y: ny,
+                        label: l,
+                    });
+                    seq += 1;
+                }
+            }
+        }
+        pending.clear();
+        let q = match queue.pop() {
+            Some(q) => q,
+            None => break,
+        };
+        if labels[(q.x, q.y)][0] != 0 || q.key > tolerance {
+            continue;
+        }
+        let li = q.label as usize - 1;
+        labels[(q.x, q.y)] = Gray([q.label]);
+        channel_values(&img[(q.x, q.y)], &mut v);
+        for c in 0..ch {
+            sums[li * ch + c] += v[c];
+        }
+        areas[li] += 1;
+        pending.push((q.x, q.y, q.label));
+    }
+    let bounds = region_bounds(&labels, count);
+    (labels, bounds)
+}
+
+/// Fills the region connected to `seed` whose pixels differ from the seed
+/// pixel by at most `tolerance` in every channel with `value`, and returns
+/// its bounds. Nothing is filled if `seed` lies outside `img`.
+pub fn flood_fill<P: Pixel>(
+    img: &mut Image<P>,
+    seed: Pointi,
+    value: P,
+    tolerance: f32,
+    conn: Connectivity,
+) -> Recti {
+    let (w, h) = img.size();
+    if seed.x < 0 || seed.y < 0 || seed.x >= w as isize || seed.y >= h as isize {
+        return Rect::new(0, 0, 0, 0);
+    }
+    let ch = P::channels();
+    let mut origin = vec![0f32; ch];
+    channel_values(&img[(seed.x as usize, seed.y as usize)], &mut origin);
+    let mut v = vec![0f32; ch];
+    let mut visited = vec![false; w * h];
+    let mut stack = vec![(seed.x as usize, seed.y as usize)];
+    visited[seed.y as usize * w + seed.x as usize] = true;
+    let mut filled = Vec::new();
+    while let Some((x, y)) = stack.pop() {
+        filled.push((x, y));
+        for (nx, ny) in neighbours(x, y, w, h, conn) {
+            if visited[ny * w + nx] {
+                continue;
+            }
+            channel_values(&img[(nx, ny)], &mut v);
+            if v.iter().zip(origin.iter()).all(|(a, b)| (a - b).abs() <= tolerance) {
+                visited[ny * w + nx] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+    let (mut x0, mut y0, mut x1, mut y1) = (w, h, 0, 0);
+    for &(x, y) in filled.iter() {
+        img[(x, y)] = value;
+        x0 = x0.min(x);
+        y0 = y0.min(y);
+        x1 = x1.max(x);
+        y1 = y1.max(y);
+    }
+    Rect::new(
+        x0 as isize,
+        y0 as isize,
+        (x1 - x0 + 1) as isize,
+        (y1 - y0 + 1) as isize,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use geo::Point;
+    use pixel::gray;
+
+    #[test]
+    fn test_watershed() {
+        // two basins separated by a ridge at x = 3
+        let img =
+            gray_image![
+            1, 2, 5, 9, 5, 2, 1;
+            0, 1, 4, 9, 4, 1, 0;
+            1, 2, 5, 9, 5, 2, 1
+        ];
+        let mut markers = ImageLabel::new(7, 3);
+        markers.zero();
+        markers[(0, 1)] = Gray([1]);
+        markers[(6, 1)] = Gray([2]);
+        let (labels, bounds) = watershed(&img, &markers, Connectivity::Four);
+        for (x, _, p) in labels.iter() {
+            if x < 3 {
+                assert_eq!(p[0], 1);
+            } else if x > 3 {
+                assert_eq!(p[0], 2);
+            } else {
+                assert!(p[0] == 1 || p[0] == 2);
+            }
+        }
+        assert_eq!(bounds.len(), 2);
+        assert_eq!((bounds[0].x, bounds[0].y, bounds[0].height), (0, 0, 3));
+        assert_eq!(bounds[1].x + bounds[1].width, 7);
+    }
+
+    #[test]
+    fn test_region_grow_and_flood_fill() {
+        let mut img =
+            gray_image![
+            10, 12, 11, 90, 91;
+            11, 10, 13, 92, 90;
+            50, 52, 51, 90, 89
+        ];
+        let mut seeds = ImageLabel::new(5, 3);
+        seeds.zero();
+        seeds[(0, 0)] = Gray([1]);
+        seeds[(4, 0)] = Gray([2]);
+        let (labels, bounds) = region_grow(&img, &seeds, 10f32, Connectivity::Four);
+        assert_eq!(labels[(2, 1)][0], 1);
+        assert_eq!(labels[(3, 2)][0], 2);
+        assert_eq!(labels[(1, 2)][0], 0);
+        assert_eq!(bounds, vec![Rect::new(0, 0, 3, 2), Rect::new(3, 0, 2, 3)]);
+
+        let rect = flood_fill(&mut img, Point::new(4, 2), gray(0), 2f32, Connectivity::Eight);
+        assert_eq!(rect, Rect::new(3, 0, 2, 3));
+        assert_eq!(img[(3, 1)], gray(92));
+        assert_eq!(img[(4, 1)], gray(0));
+        let rect = flood_fill(&mut img, Point::new(9, 9), gray(1), 2f32, Connectivity::Four);
+        assert_eq!(rect.area(), 0);
+    }
+}
@@ -0,0 +1,337 @@
+use std::f32;
+use image::{Image, ImageBGR, ImageLabel};
+use pixel::{Pixel, BGR, Gray};
+use geo::{Point, Pointf};
+use convert::{bgr_to_lab, lab_to_bgr};
+
+/// A SLIC segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Superpixel {
+    pub label: u32,
+    /// mean color, averaged in Lab space
+    pub color: BGR<u8>,
+    pub centroid: Pointf,
+    pub area: usize,
+}
+
+/// Simple linear iterative clustering (Achanta et al., 2012).
+///
+/// Clusters pixels on their Lab color and position into about `n_segments`
+/// compact regions. Higher `compactness` favours square regions over
+/// following the image colors.
+#[derive(Debug, Clone)]
+pub struct Slic {
+    n_segments: usize,
+    compactness: f32,
+    iterations: usize,
+    enforce_connectivity: bool,
+}
+
+impl Default for Slic {
+    fn default() -> Slic {
+        Slic::new(100)
+    }
+}
+
+// cluster center in (L, a, b, x, y)
+#[derive(Debug, Clone, Copy)]
+struct Center {
+    lab: [f32; 3],
+    x: f32,
+    y: f32,
+}
+
+impl Slic {
+    pub fn new(n_segments: usize) -> Slic {
+        Slic {
+            n_segments: n_segments,
+            compactness: 10f32,
+            iterations: 10,
+            enforce_connectivity: true,
+        }
+    }
+
+    pub fn compactness(mut self, m: f32) -> Self {
+        self.compactness = m;
+        self
+    }
+
+    pub fn iterations(mut self, n: usize) -> Self {
+        self.iterations = n;
+        self
+    }
+
+    /// Merges fragments that are not connected to their cluster into a
+    /// neighbouring segment, so every label is one 4-connected region.
+    pub fn enforce_connectivity(mut self, enable: bool) -> Self {
+        self.enforce_connectivity = enable;
+        self
+    }
+
+    /// Labels `img` with segments numbered from 1.
+    pub fn segment(&self, img: &ImageBGR) -> (ImageLabel, Vec<Superpixel>) {
+        let (w, h) = img.size();
+        let n = w * h;
+        let lab: Vec<[f32; 3]> = img.iter().map(|(_, _, p)| bgr_to_lab(p)).collect();
+        let step = ((n as f32 / self.n_segments.max(1) as f32).sqrt().round() as usize).max(1);
+        let mut centers = initial_centers(&lab, w, h, step);
+
+        // the first pass labels every pixel, as the seeds are `step` apart
+        let mut labels = vec![usize::max_value(); n];
+        let weight = (self.compactness / step as f32).powi(2);
+        assign(&centers, &lab, w, h, step, weight, &mut labels);
+        for _ in 0..self.iterations {
+            update_centers(&mut centers, &labels, &lab, w);
+            assign(&centers, &lab, w, h, step, weight, &mut labels);
+        }
+
+        let mut count = centers.len();
+        if self.enforce_connectivity {
+            count = relabel_connected(&mut labels, w, h, step * step / 4);
+        }
+
+        let mut sums = vec![[0f32; 5]; count];
+        let mut areas = vec![0usize; count];
+        let mut dst = ImageLabel::new(w, h);
+        for (x, y, p) in dst.iter_mut() {
+            let l = labels[y * w + x];
+            *p = Gray([l as u32 + 1]);
+            let s = &mut sums[l];
+            let v = &lab[y * w + x];
+            s[0] += v[0];
+            s[1] += v[1];
+            s[2] += v[2];
+            s[3] += x as f32;
+            s[4] += y as f32;
+            areas[l] += 1;
+        }
+        let segments = sums.iter()
+            .zip(areas.iter())
+            .enumerate()
+            .map(|(l, (s, &area))| {
+                let a = area.max(1) as f32;
+                Superpixel {
+                    label: l as u32 + 1,
+                    color: lab_to_bgr(&[s[0] / a, s[1] / a, s[2] / a]),
+                    centroid: Point::new(s[3] / a, s[4] / a),
+                    area: area,
+                }
+            })
+            .collect();
+        (dst, segments)
+    }
+}
+
+// labels the pixels within `step` of every center with the nearest one
+fn assign(
+    centers: &[Center],
+    lab: &[[f32; 3]],
+    w: usize,
+    h: usize,
+    step: usize,
+    weight: f32,
+    labels: &mut [usize],
+) {
+    let mut dist = vec![f32::INFINITY; labels.len()];
+    for (k, c) in centers.iter().enumerate() {
+        let x0 = (c.x - step as f32).max(0f32) as usize;
+        let y0 = (c.y - step as f32).max(0f32) as usize;
+        let x1 = ((c.x + step as f32) as usize + 1).min(w);
+        let y1 = ((c.y + step as f32) as usize + 1).min(h);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let i = y * w + x;
+                let dc = (0..3).map(|j| (lab[i][j] - c.lab[j]).powi(2)).sum::<f32>();
+                let ds = (x as f32 - c.x).powi(2) + (y as f32 - c.y).powi(2);
+                let d = dc + ds * weight;
+                if d < dist[i] {
+                    dist[i] = d;
+                    labels[i] = k;
+                }
+            }
+        }
+    }
+}
+
+// grid of seeds `step` apart, each moved to the lowest gradient position in
+// its 3x3 neighbourhood so it does not start on an edge
+fn initial_centers(lab: &[[f32; 3]], w: usize, h: usize, step: usize) -> Vec<Center> {
+    let gradient = |x: usize, y: usize| {
+        if x == 0 || y == 0 || x + 1 >= w || y + 1 >= h {
+            return f32::INFINITY;
+        }
+        let (l, r) = (&lab[y * w + x - 1], &lab[y * w + x + 1]);
+        let (u, d) = (&lab[(y - 1) * w + x], &lab[(y + 1) * w + x]);
+        (0..3)
+            .map(|j| (r[j] - l[j]).powi(2) + (d[j] - u[j]).powi(2))
+            .sum::<f32>()
+    };
+    let mut centers = Vec::new();
+    let mut y = step / 2;
+    while y < h {
+        let mut x = step / 2;
+        while x < w {
+            let (mut bx, mut by) = (x, y);
+            let mut best = gradient(x, y);
+            for ny in y.saturating_sub(1)..(y + 2).min(h) {
+                for nx in x.saturating_sub(1)..(x + 2).min(w) {
+                    let g = gradient(nx, ny);
+                    if g < best {
+                        best = g;
+                        bx = nx;
+                        by = ny;
+                    }
+                }
+            }
+            centers.push(Center {
+                lab: lab[by * w + bx],
+                x: bx as f32,
+                y: by as f32,
+            });
+            x += step;
+        }
+        y += step;
+    }
+    centers
+}
+
+fn update_centers(centers: &mut [Center], labels: &[usize], lab: &[[f32; 3]], w: usize) {
+    let mut sums = vec![[0f32; 5]; centers.len()];
+    let mut counts = vec![0usize; centers.len()];
+    for (i, &l) in labels.iter().enumerate() {
+        if l == usize::max_value() {
+            continue;
+        }
+        let s = &mut sums[l];
+        s[0] += lab[i][0];
+        s[1] += lab[i][1];
+        s[2] += lab[i][2];
+        s[3] += (i % w) as f32;
+        s[4] += (i / w) as f32;
+        counts[l] += 1;
+    }
+    for (c, (s, &n)) in centers.iter_mut().zip(sums.iter().zip(counts.iter())) {
+        if n > 0 {
+            let n = n as f32;
+            c.lab = [s[0] / n, s[1] / n, s[2] / n];
+            c.x = s[3] / n;
+            c.y = s[4] / n;
+        }
+    }
+}
+
+// relabels the 4-connected components of `labels` consecutively from 0,
+// merging components smaller than `min_area` into the previous adjacent
+// component; returns the component count
+fn relabel_connected(labels: &mut [usize], w: usize, h: usize, min_area: usize) -> usize {
+    const OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (1, 0), (0, 1)];
+    let n = w * h;
+    let mut out = vec![usize::max_value(); n];
+    let mut count = 0;
+    let mut component = Vec::new();
+    for start in 0..n {
+        if out[start] != usize::max_value() {
+            continue;
+        }
+        // label of an already relabelled neighbour of the component start
+        let (sx, sy) = ((start % w) as isize, (start / w) as isize);
+        let mut adjacent = None;
+        for &(dx, dy) in OFFSETS.iter() {
+            let (nx, ny) = (sx + dx, sy + dy);
+            if nx >= 0 && ny >= 0 && nx < w as isize && ny < h as isize {
+                let j = ny as usize * w + nx as usize;
+                if out[j] != usize::max_value() {
+                    adjacent = Some(out[j]);
+                }
+            }
+        }
+
+        component.clear();
+        component.push(start);
+        out[start] = count;
+        let mut k = 0;
+        while k < component.len() {
+            let i = component[k];
+            let (x, y) = ((i % w) as isize, (i / w) as isize);
+            for &(dx, dy) in OFFSETS.iter() {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= w as isize || ny >= h as isize {
+                    continue;
+                }
+                let j = ny as usize * w + nx as usize;
+                if out[j] == usize::max_value() && labels[j] == labels[start] {
+                    out[j] = count;
+                    component.push(j);
+                }
+            }
+            k += 1;
+        }
+
+        match adjacent {
+            Some(l) if component.len() < min_area => {
+                for &i in component.iter() {
+                    out[i] = l;
+                }
+            }
+            _ => count += 1,
+        }
+    }
+    labels.copy_from_slice(&out);
+    count
+}
+
+/// Paints the pixels of `img` whose right or lower neighbour lies in a
+/// different segment of `labels` with `color`.
+pub fn draw_boundaries<P: Pixel>(img: &mut Image<P>, labels: &ImageLabel, color: P) {
+    let (w, h) = labels.size();
+    assert_eq!(img.size(), (w, h));
+    for y in 0..h {
+        for x in 0..w {
+            let l = labels[(x, y)][0];
+            if (x + 1 < w && labels[(x + 1, y)][0] != l) ||
+                (y + 1 < h && labels[(x, y + 1)][0] != l)
+            {
+                img[(x, y)] = color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pixel::bgr;
+
+    #[test]
+    fn test_slic() {
+        // left half red, right half blue
+        let mut img = ImageBGR::new(40, 20);
+        for (x, _, p) in img.iter_mut() {
+            *p = if x < 20 { bgr(0, 0, 200) } else { bgr(200, 0, 0) };
+        }
+        let (labels, segments) = Slic::new(8).compactness(20f32).segment(&img);
+        assert!(segments.len() >= 4 && segments.len() <= 12, "{}", segments.len());
+        assert_eq!(segments.iter().map(|s| s.area).sum::<usize>(), 40 * 20);
+        for s in segments.iter() {
+            // no segment straddles the color edge
+            let red = s.color[2] > s.color[0];
+            for (x, _, p) in labels.iter() {
+                if p[0] == s.label {
+                    assert_eq!(x < 20, red);
+                }
+            }
+            assert!(s.centroid.x >= 0f32 && s.centroid.x < 40f32);
+        }
+
+        // without iterations the seeds still label every pixel
+        let slic = Slic::new(8).iterations(0).enforce_connectivity(false);
+        let (labels0, segments0) = slic.segment(&img);
+        assert!(labels0.iter().all(|(_, _, p)| p[0] >= 1 && p[0] as usize <= segments0.len()));
+        assert_eq!(segments0.iter().map(|s| s.area).sum::<usize>(), 40 * 20);
+
+        let mut out = img.clone();
+        draw_boundaries(&mut out, &labels, bgr(0, 255, 0));
+        assert_eq!(out[(19, 10)], bgr(0, 255, 0));
+        assert_eq!(out[(39, 19)], img[(39, 19)]);
+    }
+}
@@ -0,0 +1,124 @@
+use image::ImageBinary;
+use pixel::gray;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThinningType {
+    /// Zhang and Suen (1984)
+    ZhangSuen,
+    /// Guo and Hall (1989), which keeps diagonal lines thinner
+    GuoHall,
+}
+
+// neighbours P2..P9, clockwise from north
+const NEIGHBOURS: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+fn should_delete(p: &[bool; 8], kind: ThinningType, odd: bool) -> bool {
+    let (p2, p3, p4, p5, p6, p7, p8, p9) = (p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7]);
+    match kind {
+        ThinningType::ZhangSuen => {
+            let b = p.iter().filter(|&&v| v).count();
+            // 0 -> 1 transitions around the pixel
+            let a = (0..8).filter(|&i| !p[i] && p[(i + 1) % 8]).count();
+            let (c1, c2) = if odd {
+                (p2 && p4 && p6, p4 && p6 && p8)
+            } else {
+                (p2 && p4 && p8, p2 && p6 && p8)
+            };
+            b >= 2 && b <= 6 && a == 1 && !c1 && !c2
+        }
+        ThinningType::GuoHall => {
+            let c = (!p2 && (p3 || p4)) as u8 + (!p4 && (p5 || p6)) as u8 +
+                (!p6 && (p7 || p8)) as u8 + (!p8 && (p9 || p2)) as u8;
+            let n1 = (p9 || p2) as u8 + (p3 || p4) as u8 + (p5 || p6) as u8 + (p7 || p8) as u8;
+            let n2 = (p2 || p3) as u8 + (p4 || p5) as u8 + (p6 || p7) as u8 + (p8 || p9) as u8;
+            let n = n1.min(n2);
+            let m = if odd {
+                (p6 || p7 || !p9) && p8
+            } else {
+                (p2 || p3 || !p5) && p4
+            };
+            c == 1 && n >= 2 && n <= 3 && !m
+        }
+    }
+}
+
+/// Thins the non-zero regions of `src` to a skeleton one pixel wide,
+/// preserving their connectivity.
+pub fn thin(src: &ImageBinary, kind: ThinningType) -> ImageBinary {
+    let (w, h) = src.size();
+    let mut cur: Vec<bool> = src.iter().map(|(_, _, p)| p[0] != 0).collect();
+    let at = |cur: &[bool], x: isize, y: isize| {
+        x >= 0 && y >= 0 && x < w as isize && y < h as isize && cur[y as usize * w + x as usize]
+    };
+    let mut deleted = Vec::new();
+    loop {
+        let mut changed = false;
+        for &odd in [true, false].iter() {
+            deleted.clear();
+            for y in 0..h as isize {
+                for x in 0..w as isize {
+                    if !cur[y as usize * w + x as usize] {
+                        continue;
+                    }
+                    let mut p = [false; 8];
+                    for (k, &(dx, dy)) in NEIGHBOURS.iter().enumerate() {
+                        p[k] = at(&cur, x + dx, y + dy);
+                    }
+                    if should_delete(&p, kind, odd) {
+                        deleted.push(y as usize * w + x as usize);
+                    }
+                }
+            }
+            for &i in deleted.iter() {
+                cur[i] = false;
+            }
+            changed |= !deleted.is_empty();
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut dst = ImageBinary::new(w, h);
+    for (x, y, p) in dst.iter_mut() {
+        *p = gray(cur[y * w + x] as u8);
+    }
+    dst
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_thinning() {
+        let mut img = ImageBinary::new(12, 7);
+        img.zero();
+        for y in 2..5 {
+            for x in 1..11 {
+                img[(x, y)] = gray(1);
+            }
+        }
+        for &kind in [ThinningType::ZhangSuen, ThinningType::GuoHall].iter() {
+            let skeleton = thin(&img, kind);
+            // a horizontal bar thins to a line along its middle row
+            for (x, y, p) in skeleton.iter() {
+                if p[0] != 0 {
+                    assert_eq!(y, 3, "{:?} {} {}", kind, x, y);
+                }
+            }
+            let count = skeleton.iter().filter(|&(_, _, p)| p[0] != 0).count();
+            assert!(count >= 6, "{:?} {}", kind, count);
+            // every skeleton pixel is one pixel wide
+            assert_eq!(thin(&skeleton, kind), skeleton);
+        }
+    }
+}
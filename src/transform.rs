@@ -2,14 +2,117 @@ use image::*;
 use pixel::*;
 use num::NumCast;
 use std::ops::Index;
+use num::Zero;
 use num::traits::Bounded;
 use math::utils::*;
 use math::affine::Affine2D;
+use math::homography::Homography;
 use num::traits::ToPrimitive;
+use eye::AlterType;
+use sampler::*;
+use geo::{Point, Pointf};
+use op::stats;
+pub use op::stats::MinMaxLoc;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InterplateType {
     Nearest,
     Bilinear,
+    Bicubic,
+    Lanczos3,
+    Lanczos4,
+    /// pixel area averaging; only for resizing, the warps panic on it
+    Area,
+}
+
+// Source taps of each destination pixel along one axis, mapping pixel
+// centers onto pixel centers. Taps are clamped to `0..src_len`.
+fn resize_taps(interp: InterplateType, src_len: usize, dst_len: usize) -> Vec<Vec<(usize, f32)>> {
+    let scale = src_len as f32 / dst_len as f32;
+    let last = src_len as isize - 1;
+    let clamp = |i: isize| if i < 0 { 0 } else if i > last { last as usize } else { i as usize };
+    (0..dst_len)
+        .map(|d| if interp == InterplateType::Area {
+            // overlap of the destination pixel footprint with each source pixel
+            let lo = d as f32 * scale;
+            let hi = lo + scale;
+            let mut taps = Vec::new();
+            let mut i = lo.floor();
+            while i < hi {
+                let w = hi.min(i + 1f32) - lo.max(i);
+                if w > 0f32 {
+                    taps.push((clamp(i as isize), w / scale));
+                }
+                i += 1f32;
+            }
+            taps
+        } else {
+            let t = (d as f32 + 0.5) * scale - 0.5;
+            let (start, weights, n) = kernel_taps(interp, t);
+            weights[..n]
+                .iter()
+                .enumerate()
+                .map(|(k, &w)| (clamp(start + k as isize), w))
+                .collect()
+        })
+        .collect()
+}
+
+// separable resampling through an f32 buffer holding the horizontal pass
+fn resize_separable<T: Pixel>(
+    src: &Image<T>,
+    width: usize,
+    height: usize,
+    interp: InterplateType,
+) -> Image<T> {
+    let ch = T::channels();
+    let xtaps = resize_taps(interp, src.width(), width);
+    let ytaps = resize_taps(interp, src.height(), height);
+
+    let mut buf = vec![0f32; width * src.height() * ch];
+    for y in 0..src.height() {
+        let psrc = src.row(y);
+        for x in 0..width {
+            let off = (y * width + x) * ch;
+            for &(sx, w) in xtaps[x].iter() {
+                let p = psrc[sx].raw();
+                for c in 0..ch {
+                    buf[off + c] += p[c].to_f32().unwrap() * w;
+                }
+            }
+        }
+    }
+
+    let mut dst = Image::new(width, height);
+    let mut acc = [0f32; MAX_CHANNEL_COUNT];
+    let mut out = [T::Subpixel::zero(); MAX_CHANNEL_COUNT];
+    for y in 0..height {
+        let pdst = dst.row_mut(y);
+        for x in 0..width {
+            acc.iter_mut().for_each(|a| *a = 0f32);
+            for &(sy, w) in ytaps[y].iter() {
+                let off = (sy * width + x) * ch;
+                for c in 0..ch {
+                    acc[c] += buf[off + c] * w;
+                }
+            }
+            for c in 0..ch {
+                out[c] = saturate(acc[c]);
+            }
+            pdst[x] = T::from_raw(&out[..ch]);
+        }
+    }
+    dst
+}
+
+pub fn resize_bicubic<T: Pixel>(src: &Image<T>, width: usize, height: usize) -> Image<T> {
+    resize_separable(src, width, height, InterplateType::Bicubic)
+}
+
+/// Resizes by averaging the source pixels covered by each destination
+/// pixel, which avoids aliasing when shrinking.
+pub fn resize_area<T: Pixel>(src: &Image<T>, width: usize, height: usize) -> Image<T> {
+    resize_separable(src, width, height, InterplateType::Area)
 }
 
 pub fn resize_nearest<T: Pixel>(src: &Image<T>, width: usize, height: usize) -> Image<T> {
@@ -19,7 +122,7 @@ pub fn resize_nearest<T: Pixel>(src: &Image<T>, width: usize, height: usize) ->
     let mut xidx: Vec<usize> = Vec::with_capacity(width as usize);
     for w in 0..width as usize {
         xidx.push(clipped_round(
-            w as f32 * xscale,
+            (w as f32 + 0.5) * xscale - 0.5,
             0,
             src.width() as i32 - 1,
         ) as usize);
@@ -27,7 +130,7 @@ pub fn resize_nearest<T: Pixel>(src: &Image<T>, width: usize, height: usize) ->
     for h in 0..height {
         let pdst = dst.row_mut(h);
         let psrc = src.row(clipped_round(
-            h as f32 * yscale,
+            (h as f32 + 0.5) * yscale - 0.5,
             0,
             src.height() as i32 - 1,
         ) as usize);
@@ -39,40 +142,27 @@ pub fn resize_nearest<T: Pixel>(src: &Image<T>, width: usize, height: usize) ->
 }
 
 pub fn resize_bilinear<T: Pixel>(src: &Image<T>, width: usize, height: usize) -> Image<T> {
+    let ch = T::channels();
+    let xtaps = resize_taps(InterplateType::Bilinear, src.width(), width);
+    let ytaps = resize_taps(InterplateType::Bilinear, src.height(), height);
     let mut dst = Image::new(width, height);
-    let yscale: f32 = src.height() as f32 / height as f32;
-    let xscale: f32 = src.width() as f32 / width as f32;
-
-    let mut x_0: Vec<usize> = Vec::with_capacity(width as usize);
-    let mut x_1: Vec<usize> = Vec::with_capacity(width as usize);
-    let mut d_0: Vec<f32> = Vec::with_capacity(width as usize);
-
-    for w in 0..width as usize {
-        let mid = w as f32 * xscale;
-        let l = mid.floor();
-        let r = mid.ceil();
-        let d = r - mid;
-        x_0.push(clipped_round(l, 0, src.width() as i32 - 1) as usize);
-        x_1.push(clipped_round(r, 0, src.width() as i32 - 1) as usize);
-        d_0.push(d);
-    }
+    let mut out = [T::Subpixel::zero(); MAX_CHANNEL_COUNT];
     for h in 0..height {
         let pdst = dst.row_mut(h);
-
-        let mid = h as f32 * yscale;
-        let t = mid.floor();
-        let b = mid.ceil();
-        let dy = b - mid;
-
-        let psrc0 = src.row(clipped_round(t, 0, src.height() as i32 - 1) as usize);
-        let psrc1 = src.row(clipped_round(b, 0, src.height() as i32 - 1) as usize);
-        for w in 0..width as usize {
-            let x0 = x_0[w];
-            let x1 = x_1[w];
-            //let a = psrc0[x0].blend(psrc0[x1], d_0[w]);
-            //let b = psrc1[x0].blend(psrc1[x1], d_0[w]);
-            //pdst[w] = a.blend(b, dy);
-            pdst[w] = psrc0[x0].blend4(psrc0[x1], psrc1[x0], psrc1[x1], d_0[w], dy);
+        let (y0, v0) = ytaps[h][0];
+        let (y1, v1) = ytaps[h][1];
+        let (psrc0, psrc1) = (src.row(y0), src.row(y1));
+        for w in 0..width {
+            let (x0, u0) = xtaps[w][0];
+            let (x1, u1) = xtaps[w][1];
+            let (a, b) = (psrc0[x0].raw(), psrc0[x1].raw());
+            let (c, d) = (psrc1[x0].raw(), psrc1[x1].raw());
+            for k in 0..ch {
+                let top = a[k].to_f32().unwrap() * u0 + b[k].to_f32().unwrap() * u1;
+                let bottom = c[k].to_f32().unwrap() * u0 + d[k].to_f32().unwrap() * u1;
+                out[k] = saturate(top * v0 + bottom * v1);
+            }
+            pdst[w] = T::from_raw(&out[..ch]);
         }
     }
     dst
@@ -87,9 +177,14 @@ pub fn resize<T: Pixel>(
     match interp {
         InterplateType::Nearest => resize_nearest(src, width, height),
         InterplateType::Bilinear => resize_bilinear(src, width, height),
+        _ => resize_separable(src, width, height, interp),
     }
 }
 
+/// Warps `src` with `affine`. Pixels mapped outside `src` take the value of
+/// the nearest edge pixel; see `warp_affine` for other borders.
+///
+/// Panics if `interp` is `Area`.
 pub fn warp_perspective<T: Pixel>(
     src: &Image<T>,
     width: usize,
@@ -97,37 +192,200 @@ pub fn warp_perspective<T: Pixel>(
     affine: &Affine2D,
     interp: InterplateType,
 ) -> Image<T> {
+    warp(
+        src,
+        width,
+        height,
+        |x, y| {
+            let coord = affine.apply_inv([x, y, 1f32]);
+            (coord[0] / coord[2], coord[1] / coord[2])
+        },
+        interp,
+        AlterType::Extend,
+    )
+}
+
+// `map_inv` maps destination coordinates back into `src`
+fn warp_with<T, S, F>(
+    src: &Image<T>,
+    width: usize,
+    height: usize,
+    map_inv: F,
+    sampler: S,
+) -> Image<T>
+where
+    T: Pixel,
+    S: Sampler<T>,
+    F: Fn(f32, f32) -> (f32, f32),
+{
     let mut dst: Image<T> = Image::new(width, height);
     for h in 0..height {
         let pdst = dst.row_mut(h);
         for w in 0..width {
-            let coord = affine.apply_inv([w as f32, h as f32, 1f32]);
-            let sx = coord[0] / coord[2];
-            let sy = coord[1] / coord[2];
-            match interp {
-                InterplateType::Nearest => {
-                    let ix = sx.round() as i32;
-                    let iy = sy.round() as i32;
-                    if ix >= 0 && ix < src.width() as i32 && iy >= 0 && iy < src.height() as i32 {
-                        pdst[w as usize] = src[(ix as usize, iy as usize)];
-                    }
-                }
-                InterplateType::Bilinear => {
-                    let u = sx.ceil() - sx;
-                    let v = sy.ceil() - sy;
-                    let x0 = clip(sx.floor() as i32, 0, src.width() as i32 - 1) as usize;
-                    let y0 = clip(sy.floor() as i32, 0, src.height() as i32 - 1) as usize;
-                    let x1 = clip(sx.ceil() as i32, 0, src.width() as i32 - 1) as usize;
-                    let y1 = clip(sy.ceil() as i32, 0, src.height() as i32 - 1) as usize;
-                    pdst[w as usize] =
-                        src[(x0, y0)].blend4(src[(x1, y0)], src[(x0, y1)], src[(x1, y1)], u, v);
-                }
-            }
+            let (sx, sy) = map_inv(w as f32, h as f32);
+            pdst[w] = sampler.sample(src, Point::new(sx, sy));
         }
     }
     dst
 }
 
+fn warp<T: Pixel, F: Fn(f32, f32) -> (f32, f32)>(
+    src: &Image<T>,
+    width: usize,
+    height: usize,
+    map_inv: F,
+    interp: InterplateType,
+    border: AlterType<T>,
+) -> Image<T> {
+    match interp {
+        InterplateType::Nearest => {
+            warp_with(src, width, height, map_inv, NearestSampler::new().alter(border))
+        }
+        InterplateType::Bilinear => {
+            warp_with(src, width, height, map_inv, BilinearSampler::new().alter(border))
+        }
+        InterplateType::Bicubic => {
+            warp_with(src, width, height, map_inv, BicubicSampler::new().alter(border))
+        }
+        InterplateType::Lanczos3 => {
+            warp_with(src, width, height, map_inv, LanczosSampler::new(3).alter(border))
+        }
+        InterplateType::Lanczos4 => {
+            warp_with(src, width, height, map_inv, LanczosSampler::new(4).alter(border))
+        }
+        InterplateType::Area => panic!("area interpolation is only supported by resize"),
+    }
+}
+
+/// Applies the 2x3 affine matrix `m`, which maps source coordinates to
+/// destination coordinates, producing a `width` x `height` image. Pixels
+/// sampled outside `src` are produced according to `border`.
+///
+/// Panics if `m` is not invertible or `interp` is `Area`.
+pub fn warp_affine<T: Pixel>(
+    src: &Image<T>,
+    m: &[[f32; 3]; 2],
+    width: usize,
+    height: usize,
+    interp: InterplateType,
+    border: AlterType<T>,
+) -> Image<T> {
+    let affine = Affine2D::from_matrix(m).expect("affine matrix is not invertible");
+    warp(
+        src,
+        width,
+        height,
+        |x, y| {
+            let coord = affine.apply_inv([x, y, 1f32]);
+            (coord[0], coord[1])
+        },
+        interp,
+        border,
+    )
+}
+
+/// Applies the projective transform `homography`, which maps source
+/// coordinates to destination coordinates, producing a `width` x `height`
+/// image. Pixels sampled outside `src` are produced according to `border`.
+pub fn warp_homography<T: Pixel>(
+    src: &Image<T>,
+    homography: &Homography,
+    width: usize,
+    height: usize,
+    interp: InterplateType,
+    border: AlterType<T>,
+) -> Image<T> {
+    warp(
+        src,
+        width,
+        height,
+        |x, y| {
+            let p = homography.map_point_inv(Point::new(x, y));
+            (p.x, p.y)
+        },
+        interp,
+        border,
+    )
+}
+
+/// Samples `src` at the coordinates given per destination pixel by `map_x`
+/// and `map_y`; the result has the size of the maps. Pixels sampled outside
+/// `src` are produced according to `border`.
+pub fn remap<T: Pixel>(
+    src: &Image<T>,
+    map_x: &ImageGrayf,
+    map_y: &ImageGrayf,
+    interp: InterplateType,
+    border: AlterType<T>,
+) -> Image<T> {
+    assert_eq!(map_x.size(), map_y.size());
+    warp(
+        src,
+        map_x.width(),
+        map_x.height(),
+        |x, y| {
+            let pos = (x as usize, y as usize);
+            (map_x[pos][0], map_y[pos][0])
+        },
+        interp,
+        border,
+    )
+}
+
+/// Matrix rotating by `angle` radians counterclockwise (as displayed) around
+/// `center` and scaling by `scale`, suitable for `warp_affine`.
+pub fn rotation_matrix(angle: f32, center: Pointf, scale: f32) -> [[f32; 3]; 2] {
+    let a = scale * angle.cos();
+    let b = scale * angle.sin();
+    [
+        [a, b, (1f32 - a) * center.x - b * center.y],
+        [-b, a, b * center.x + (1f32 - a) * center.y],
+    ]
+}
+
+/// Rotates `src` by an arbitrary `angle` (radians, counterclockwise as
+/// displayed) around `center`, scaling by `scale`.
+///
+/// The output has the size of `src`, unless `expand` is set, in which case
+/// it grows to hold the whole transformed image.
+pub fn rotate_by<T: Pixel>(
+    src: &Image<T>,
+    angle: f32,
+    center: Pointf,
+    scale: f32,
+    expand: bool,
+    interp: InterplateType,
+    border: AlterType<T>,
+) -> Image<T> {
+    let mut m = rotation_matrix(angle, center, scale);
+    if !expand {
+        return warp_affine(src, &m, src.width(), src.height(), interp, border);
+    }
+    // outer corners of the corner pixels, as `m` maps pixel centers
+    let (w, h) = (src.width() as f32 - 0.5, src.height() as f32 - 0.5);
+    let corners = [
+        Point::new(-0.5f32, -0.5f32),
+        Point::new(w, -0.5f32),
+        Point::new(-0.5f32, h),
+        Point::new(w, h),
+    ];
+    let (mut x0, mut y0) = (::std::f32::MAX, ::std::f32::MAX);
+    let (mut x1, mut y1) = (::std::f32::MIN, ::std::f32::MIN);
+    for p in corners.iter() {
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2];
+        x0 = x0.min(x);
+        y0 = y0.min(y);
+        x1 = x1.max(x);
+        y1 = y1.max(y);
+    }
+    m[0][2] -= x0 + 0.5;
+    m[1][2] -= y0 + 0.5;
+    let width = (x1 - x0).round() as usize;
+    let height = (y1 - y0).round() as usize;
+    warp_affine(src, &m, width, height, interp, border)
+}
+
 pub fn flip_vertical<T: Pixel>(src: &Image<T>) -> Image<T> {
     let mut dst = Image::new(src.width(), src.height());
     for h in 0..src.height() {
@@ -209,8 +467,11 @@ where
     dst
 }
 
-//pub fn max<T: Pixel>(src: &Image<T>) -> (usize, usize, T) {
-//}
+/// Per channel minimum and maximum of `src` with their locations, `None` if
+/// `src` is empty; see `op::stats::min_max_loc` to restrict them to a mask.
+pub fn min_max_loc<T: Pixel>(src: &Image<T>) -> Option<Vec<MinMaxLoc<T::Subpixel>>> {
+    stats::min_max_loc(src, None)
+}
 
 #[derive(Debug, Clone)]
 pub enum RotateType {
@@ -272,8 +533,9 @@ mod test {
     use image::ImageBGRA;
     use imageio::ImageIO;
     use imageio::FreeImageIO;
-    use geo::*;
     use math::affine::Affine2D;
+    use eye::AlterType;
+    use pixel::gray;
 
     #[test]
     fn test_resize() {
@@ -293,6 +555,60 @@ mod test {
         FreeImageIO::save(&target, &dst).unwrap();
     }
 
+    #[test]
+    fn test_resize_interpolation() {
+        let img = gray_image![0, 100];
+        let out = resize(&img, 4, 1, InterplateType::Bilinear);
+        assert_eq!(out, gray_image![0, 25, 75, 100]);
+
+        let img =
+            gray_image![
+            0, 4, 8, 8;
+            4, 8, 8, 8;
+            100, 100, 0, 10;
+            100, 100, 20, 30
+        ];
+        let out = resize_area(&img, 2, 2);
+        assert_eq!(
+            out,
+            gray_image![
+            4, 8;
+            100, 15
+        ]
+        );
+
+        let mut flat = ImageGray::new(7, 5);
+        flat.fill(&gray(200));
+        for interp in [
+            InterplateType::Nearest,
+            InterplateType::Bicubic,
+            InterplateType::Lanczos3,
+            InterplateType::Lanczos4,
+            InterplateType::Area,
+        ].iter()
+        {
+            let out = resize(&flat, 17, 3, *interp);
+            assert!(out.iter().all(|(_, _, p)| *p == gray(200)));
+        }
+
+        // overshoot at the step is saturated rather than wrapped
+        let step = gray_image![0, 0, 255, 255];
+        let out = resize(&step, 13, 1, InterplateType::Lanczos4);
+        assert_eq!(out[(3, 0)], gray(0));
+        assert_eq!(out[(6, 0)], gray(128));
+        assert_eq!(out[(9, 0)], gray(255));
+
+        let path = Path::new("./tests/cat.jpg");
+        let img: ImageBGRA = FreeImageIO::from_path(&path).unwrap();
+        let dst = resize(&img, 60, 40, InterplateType::Area);
+        let target = Path::new("/tmp/test-resize-out3.jpg");
+        FreeImageIO::save(&target, &dst).unwrap();
+
+        let dst = resize(&img, 600, 400, InterplateType::Lanczos3);
+        let target = Path::new("/tmp/test-resize-out4.jpg");
+        FreeImageIO::save(&target, &dst).unwrap();
+    }
+
     #[test]
     fn test_warp() {
         let path = Path::new("./tests/cat.jpg");
@@ -308,6 +624,145 @@ mod test {
         let out = warp_perspective(&img, 300, 300, &aff, InterplateType::Bilinear);
         let target = Path::new("/tmp/test-affine-out2.jpg");
         FreeImageIO::save(&target, &out).unwrap();
+
+        let out = warp_perspective(&img, 300, 300, &aff, InterplateType::Bicubic);
+        let target = Path::new("/tmp/test-affine-out3.jpg");
+        FreeImageIO::save(&target, &out).unwrap();
+
+        // shifted right by one, the left column repeats the edge
+        let img = gray_image![10, 20; 30, 40];
+        let src = vec![Pointf::new(0f32, 0f32), Pointf::new(1f32, 0f32), Pointf::new(0f32, 1f32)];
+        let dst = vec![Pointf::new(1f32, 0f32), Pointf::new(2f32, 0f32), Pointf::new(1f32, 1f32)];
+        let aff = Affine2D::affine_from_points(&src, &dst).unwrap();
+        let out = warp_perspective(&img, 2, 2, &aff, InterplateType::Bilinear);
+        assert_eq!(out, gray_image![10, 10; 30, 30]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_warp_area() {
+        let img = gray_image![1, 2; 3, 4];
+        let m = [[1f32, 0f32, 0f32], [0f32, 1f32, 0f32]];
+        warp_affine(&img, &m, 2, 2, InterplateType::Area, AlterType::Extend);
+    }
+
+    #[test]
+    fn test_rotate_by() {
+        let img =
+            gray_image![
+            1, 2, 3;
+            4, 5, 6
+        ];
+        let center = Pointf::new(1f32, 0.5f32);
+        let out = rotate_by(
+            &img,
+            ::std::f32::consts::PI,
+            center,
+            1f32,
+            false,
+            InterplateType::Nearest,
+            AlterType::Constant(gray(0)),
+        );
+        assert_eq!(
+            out,
+            gray_image![
+            6, 5, 4;
+            3, 2, 1
+        ]
+        );
+
+        let out = rotate_by(
+            &img,
+            ::std::f32::consts::FRAC_PI_2,
+            center,
+            1f32,
+            true,
+            InterplateType::Nearest,
+            AlterType::Extend,
+        );
+        assert_eq!(out.size(), (2, 3));
+        assert_eq!(out[(0, 0)], gray(3));
+        assert_eq!(out[(1, 2)], gray(4));
+        assert_eq!(out[(1, 0)], gray(6));
+
+        let path = Path::new("./tests/cat.jpg");
+        let img: ImageBGRA = FreeImageIO::from_path(&path).unwrap();
+        let center = Pointf::new(img.width() as f32 / 2f32, img.height() as f32 / 2f32);
+        let out = rotate_by(
+            &img,
+            0.5,
+            center,
+            0.8,
+            true,
+            InterplateType::Bilinear,
+            AlterType::Mirror,
+        );
+        let target = Path::new("/tmp/test-rotate-by-out1.jpg");
+        FreeImageIO::save(&target, &out).unwrap();
+    }
+
+    #[test]
+    fn test_warp_homography() {
+        let img =
+            gray_image![
+            1, 2;
+            3, 4
+        ];
+        let shift = Homography::from_matrix(
+            &[[1f32, 0f32, 1f32], [0f32, 1f32, 0f32], [0f32, 0f32, 1f32]],
+        ).unwrap();
+        let out = warp_homography(
+            &img,
+            &shift,
+            3,
+            2,
+            InterplateType::Nearest,
+            AlterType::Constant(gray(9)),
+        );
+        assert_eq!(
+            out,
+            gray_image![
+            9, 1, 2;
+            9, 3, 4
+        ]
+        );
+
+        // rectify a document-like quadrilateral
+        let path = Path::new("./tests/cat.jpg");
+        let img: ImageBGRA = FreeImageIO::from_path(&path).unwrap();
+        let (w, h) = (img.width() as f32, img.height() as f32);
+        let quad = vec![
+            Pointf::new(w * 0.1, h * 0.2),
+            Pointf::new(w * 0.9, h * 0.1),
+            Pointf::new(w * 0.8, h * 0.9),
+            Pointf::new(w * 0.2, h * 0.8),
+        ];
+        let rect = vec![
+            Pointf::new(0f32, 0f32),
+            Pointf::new(300f32, 0f32),
+            Pointf::new(300f32, 200f32),
+            Pointf::new(0f32, 200f32),
+        ];
+        let hom = Homography::from_points(&quad, &rect).unwrap();
+        let out = warp_homography(
+            &img,
+            &hom,
+            300,
+            200,
+            InterplateType::Bilinear,
+            AlterType::Extend,
+        );
+        let target = Path::new("/tmp/test-homography-out1.jpg");
+        FreeImageIO::save(&target, &out).unwrap();
+    }
+
+    #[test]
+    fn test_min_max_loc() {
+        let img = gray_image![3, 9; 1, 9];
+        let loc = min_max_loc(&img).unwrap()[0];
+        assert_eq!((loc.min, loc.min_loc), (1, Point::new(0, 1)));
+        assert_eq!((loc.max, loc.max_loc), (9, Point::new(1, 0)));
+        assert!(min_max_loc(&ImageGray::new(0, 0)).is_none());
     }
 
     #[test]